@@ -1,7 +1,9 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote, ToTokens};
-use syn::{LitByteStr, LitStr, parse_macro_input, Ident, Token, ItemFn, FnArg, Type, Pat, ReturnType};
 use syn::parse::{Parse, ParseStream};
+use syn::{
+    parse_macro_input, FnArg, Ident, ItemFn, LitByteStr, LitStr, Pat, ReturnType, Token, Type,
+};
 
 struct Attributes {
     name: Ident,
@@ -10,14 +12,14 @@ struct Attributes {
 
 enum MaybeByteStrLit {
     Str(LitStr),
-    Byte(LitByteStr)
+    Byte(LitByteStr),
 }
 
 impl MaybeByteStrLit {
     fn as_bytes_token(&self) -> proc_macro2::TokenStream {
         match self {
             MaybeByteStrLit::Str(lit) => quote!(#lit.as_bytes()),
-            MaybeByteStrLit::Byte(lit) => quote!(#lit)
+            MaybeByteStrLit::Byte(lit) => quote!(#lit),
         }
     }
 }
@@ -27,14 +29,12 @@ impl Parse for Attributes {
         let name: Ident = input.parse()?;
         input.parse::<Token![,]>()?;
 
-        let topic = input.parse::<LitStr>()
+        let topic = input
+            .parse::<LitStr>()
             .map(MaybeByteStrLit::Str)
             .or_else(|_| input.parse::<LitByteStr>().map(MaybeByteStrLit::Byte))?;
 
-        Ok(Attributes {
-            name,
-            topic
-        })
+        Ok(Attributes { name, topic })
     }
 }
 
@@ -50,8 +50,8 @@ fn allowed_types(ty: &Type) -> bool {
         Type::Group(group) => allowed_types(group.elem.as_ref()),
         Type::Paren(paren) => allowed_types(paren.elem.as_ref()),
         Type::Path(_) => true,
-        Type::Tuple(tuple) => tuple.elems.iter().all(|ty| allowed_types(ty)),
-        _ => false
+        Type::Tuple(tuple) => tuple.elems.iter().all(allowed_types),
+        _ => false,
     }
 }
 
@@ -60,7 +60,10 @@ pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
     let attr = parse_macro_input!(attr as Attributes);
     let func = parse_macro_input!(item as ItemFn);
 
-    let args: Vec<HandlerArg> = func.sig.inputs.iter()
+    let args: Vec<HandlerArg> = func
+        .sig
+        .inputs
+        .iter()
         .map(|input| match input {
             FnArg::Receiver(_) => {
                 panic!("cannot use &self/&mut self in handler");
@@ -74,7 +77,7 @@ pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
                         name: ident.ident.to_owned(),
                         ty: arg.ty.to_owned(),
                     },
-                    _ => panic!("invalid arg identifier")
+                    _ => panic!("invalid arg identifier"),
                 }
             }
         })
@@ -92,17 +95,66 @@ pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
     } else if !cfg!(feature = "async") && is_async {
         panic!("async handler used when async feature disabled");
     }
-    let async_trait = if cfg!(feature = "async") { quote!(#[::comet_eventbus::async_trait]) } else { quote!() };
-    let async_token = if cfg!(feature = "async") { quote!(async) } else { quote!() };
-    let await_token = if cfg!(feature = "async") { quote!(.await) } else { quote!() };
+    let async_trait = if cfg!(feature = "async") {
+        quote!(#[::comet_eventbus::async_trait])
+    } else {
+        quote!()
+    };
+    let async_token = if cfg!(feature = "async") {
+        quote!(async)
+    } else {
+        quote!()
+    };
+    let await_token = if cfg!(feature = "async") {
+        quote!(.await)
+    } else {
+        quote!()
+    };
+    let listener_trait = if cfg!(feature = "async") {
+        quote!(Listener)
+    } else {
+        quote!(SyncListener)
+    };
+    let register_fn = if cfg!(feature = "async") {
+        quote!(register)
+    } else {
+        quote!(register_blocking)
+    };
+    let post_fn = if cfg!(feature = "async") {
+        quote!(post)
+    } else {
+        quote!(post_blocking)
+    };
+    let unregistering = if cfg!(feature = "async") {
+        quote!(self.eventbus.unregister(listener).await)
+    } else {
+        quote!(listener.unregister_blocking())
+    };
     let return_type = match func.sig.output {
         ReturnType::Default => quote!(()),
         ReturnType::Type(_, ref ty) => ty.into_token_stream(),
     };
+    let call_return_type = quote!(Result<#return_type, ::comet_eventbus::PostError>);
+    let posting_request = quote! {
+        if let Err(error) = self.eventbus.#post_fn(&::comet_eventbus::Event::new(self.base_topic.clone(), request))#await_token {
+            #unregistering;
+            return Err(error);
+        }
+    };
+    let returning = quote!(Ok(response));
 
-    let args_with_type: Vec<_> = args.iter().map(|HandlerArg { name, ty }| quote!(#name: #ty)).collect();
-    let args_only_name: Vec<_> = args.iter().map(|HandlerArg { name, .. }| quote!(#name)).collect();
-    let calling_args: Vec<_> = args.iter().map(|HandlerArg { name, .. }| quote!(request.#name)).collect();
+    let args_with_type: Vec<_> = args
+        .iter()
+        .map(|HandlerArg { name, ty }| quote!(#name: #ty))
+        .collect();
+    let args_only_name: Vec<_> = args
+        .iter()
+        .map(|HandlerArg { name, .. }| quote!(#name))
+        .collect();
+    let calling_args: Vec<_> = args
+        .iter()
+        .map(|HandlerArg { name, .. }| quote!(request.#name))
+        .collect();
 
     let defines = quote! {
         #[derive(Clone)]
@@ -135,15 +187,15 @@ pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let listener = quote! {
         #async_trait
-        impl ::comet_eventbus::Listener<#request_name> for #service_name {
+        impl ::comet_eventbus::#listener_trait<#request_name> for #service_name {
             #async_token fn handle(&self, event: &::comet_eventbus::Event<#request_name>) -> Result<(), ::comet_eventbus::ListenerError> {
                 #func
                 let request = event.to_owned().into_inner();
                 #calling
-                self.eventbus.post(&::comet_eventbus::Event::new(
+                self.eventbus.#post_fn(&::comet_eventbus::Event::new(
                     request.reply_topic,
                     #response_name { inner }
-                ))#await_token;
+                ))#await_token?;
                 Ok(())
             }
         }
@@ -159,13 +211,13 @@ pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
             }
 
             pub #async_token fn register(&self) {
-                self.eventbus.register(self.base_topic.clone(), self.clone())#await_token;
+                self.eventbus.#register_fn(self.base_topic.clone(), self.clone())#await_token;
             }
 
             pub #async_token fn call(
                 &self,
                 #(#args_with_type,)*
-            ) -> #return_type {
+            ) -> #call_return_type {
                 struct OneTimeListener {
                     tx: ::std::sync::Mutex<::std::sync::mpsc::Sender<#return_type>>,
                 }
@@ -183,7 +235,7 @@ pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
                 }
 
                 #async_trait
-                impl ::comet_eventbus::Listener<#response_name> for OneTimeListener {
+                impl ::comet_eventbus::#listener_trait<#response_name> for OneTimeListener {
                     #async_token fn handle(&self, event: &::comet_eventbus::Event<#response_name>) -> Result<(), ::comet_eventbus::ListenerError> {
                         let response = event.to_owned().into_inner();
                         self.tx
@@ -201,11 +253,11 @@ pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
                     #(#args_only_name,)*
                     reply_topic: reply_topic.clone(),
                 };
-                let listener = self.eventbus.register(reply_topic.clone(), listener)#await_token;
-                self.eventbus.post(&::comet_eventbus::Event::new(reply_topic, request))#await_token;
+                let listener = self.eventbus.#register_fn(reply_topic.clone(), listener)#await_token;
+                #posting_request
                 let response = rx.recv().unwrap();
-                self.eventbus.unregister(listener)#await_token;
-                response
+                #unregistering;
+                #returning
             }
         }
     };
@@ -217,4 +269,4 @@ pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
 
         #implement
     })
-}
\ No newline at end of file
+}
@@ -1,6 +1,6 @@
 use crate::TopicKey;
-use std::fmt::{Debug, Formatter};
-use std::ops::{Deref, DerefMut};
+use core::fmt::{Debug, Formatter};
+use core::ops::{Deref, DerefMut};
 
 /// An `Event` for passing
 pub struct Event<T> {
@@ -17,6 +17,11 @@ impl<T> Event<T> {
         }
     }
 
+    /// get the key of the topic the event is posted to
+    pub fn get_key(&self) -> &TopicKey {
+        &self.topic
+    }
+
     /// into inner message
     pub fn into_inner(self) -> T {
         self.message
@@ -38,8 +43,8 @@ impl<T> DerefMut for Event<T> {
 }
 
 impl<T: Debug> Debug for Event<T> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct(format!("Event<{}>", std::any::type_name::<T>()).as_str())
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct(alloc::format!("Event<{}>", core::any::type_name::<T>()).as_str())
             .field("topic", &self.topic)
             .field("message", &&self.message)
             .finish()
@@ -0,0 +1,37 @@
+use crate::{Eventbus, TopicKey};
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+
+/// An `EventListener` wrapper for `Listener`
+pub struct EventListener<T> {
+    pub(crate) topic: TopicKey,
+    pub(crate) id: u64,
+    pub(crate) bus: Eventbus,
+    pub(crate) _handler: PhantomData<T>,
+}
+
+impl<T: 'static> EventListener<T> {
+    /// get the id of the listener
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// get the key of the subscribed topic
+    pub fn get_key(&self) -> &TopicKey {
+        &self.topic
+    }
+
+    /// shorthand for unregister listener from eventbus
+    pub fn unregister(self) {
+        self.bus.clone().unregister(self)
+    }
+}
+
+impl<T> Debug for EventListener<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct(alloc::format!("EventListener<{}>", core::any::type_name::<T>()).as_str())
+            .field("topic", &self.topic)
+            .field("id", &self.id)
+            .finish()
+    }
+}
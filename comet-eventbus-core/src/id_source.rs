@@ -0,0 +1,38 @@
+use spin::Mutex;
+
+/// Source of listener ids and random topic keys
+///
+/// Ids only need to be unique within an `Eventbus`. On targets with a hardware random number
+/// generator, it can be wrapped to get ids which are hard to guess.
+pub trait IdSource: Send + Sync + 'static {
+    /// get the next id
+    fn next_id(&self) -> u64;
+}
+
+/// `IdSource` counting up from 1, the default of an `Eventbus`
+///
+/// It uses a mutex instead of an `AtomicU64`, which is missing on 32 bits targets.
+#[derive(Debug, Default)]
+pub struct SequentialIds(Mutex<u64>);
+
+impl IdSource for SequentialIds {
+    fn next_id(&self) -> u64 {
+        let mut last = self.0.lock();
+        *last = last.wrapping_add(1);
+        *last
+    }
+}
+
+/// `IdSource` backed by `rand::thread_rng`
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomIds;
+
+#[cfg(feature = "std")]
+impl IdSource for RandomIds {
+    fn next_id(&self) -> u64 {
+        use rand::{thread_rng, RngCore};
+
+        thread_rng().next_u64()
+    }
+}
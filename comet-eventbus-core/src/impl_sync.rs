@@ -0,0 +1,125 @@
+use crate::{Event, EventListener, Eventbus, ListenerError, Topic, TopicKey};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::marker::PhantomData;
+
+/// Event listener
+///
+/// `comet-eventbus` re-exports it as `SyncListener`, which can be registered along with async
+/// listeners. It is called inline on the posting context, so it should not block for long.
+///
+/// Note: the struct which implements `Listener` need to be `Send` and `Sync`
+pub trait Listener<T>: Send + Sync + 'static {
+    /// handler callback to process event
+    fn handle(&self, _: &Event<T>) -> Result<(), ListenerError>;
+
+    /// human readable name of the listener
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// called once the listener is registered to a topic
+    fn on_register(&self, _topic: &TopicKey, _listener_id: u64) {}
+
+    /// called once the listener is unregistered from a topic
+    fn on_unregister(&self, _topic: &TopicKey, _listener_id: u64) {}
+
+    /// called when the eventbus shuts down, after events in flight are delivered
+    ///
+    /// Only the eventbus of `comet-eventbus` shuts down, the one of this crate never calls it.
+    fn on_bus_shutdown(&self, _topic: &TopicKey, _listener_id: u64) {}
+}
+
+/// listeners of a message type on a topic, by id
+type TopicListeners<T> = BTreeMap<u64, Arc<dyn Listener<T>>>;
+
+impl Eventbus {
+    /// create a `Topic` using a topic key
+    pub fn create_topic<T: 'static, K: Into<TopicKey>>(&self, topic_key: K) -> Topic<T> {
+        Topic {
+            key: topic_key.into(),
+            bus: self.clone(),
+            _message: PhantomData,
+        }
+    }
+
+    /// register a listener to eventbus
+    pub fn register<T: 'static, K: Into<TopicKey>, L: Listener<T>>(
+        &self,
+        topic_key: K,
+        listener: L,
+    ) -> EventListener<T> {
+        let event_listener = EventListener {
+            topic: topic_key.into(),
+            id: self.inner.ids.next_id(),
+            bus: self.clone(),
+            _handler: PhantomData,
+        };
+        trace!("add event_listener: {:?}", event_listener);
+        let listener = Arc::new(listener);
+        self.inner
+            .listeners
+            .lock()
+            .entry((TypeId::of::<T>(), event_listener.topic.clone()))
+            .or_insert_with(|| Box::new(TopicListeners::<T>::new()))
+            .downcast_mut::<TopicListeners<T>>()
+            .expect("listeners are keyed by message type")
+            .insert(event_listener.id, listener.clone());
+        // called without the lock, so that the hook may use the eventbus
+        listener.on_register(&event_listener.topic, event_listener.id);
+        event_listener
+    }
+
+    /// unregister a listener
+    pub fn unregister<T: 'static>(&self, event_listener: EventListener<T>) {
+        let key = (TypeId::of::<T>(), event_listener.topic);
+        let removed = {
+            let mut listeners = self.inner.listeners.lock();
+            let Some(topic_listeners) = listeners
+                .get_mut(&key)
+                .and_then(|listeners| listeners.downcast_mut::<TopicListeners<T>>())
+            else {
+                return;
+            };
+            let removed = topic_listeners.remove(&event_listener.id);
+            if topic_listeners.is_empty() {
+                listeners.remove(&key);
+            }
+            removed
+        };
+        if let Some(listener) = removed {
+            listener.on_unregister(&key.1, event_listener.id);
+        }
+    }
+
+    /// post an event to eventbus
+    ///
+    /// Listeners are called one after another on the current context. They are taken out of
+    /// the registry first, so they may register and unregister listeners or post events.
+    pub fn post<T: 'static>(&self, event: &Event<T>) {
+        let listeners: Vec<_> = match self
+            .inner
+            .listeners
+            .lock()
+            .get(&(TypeId::of::<T>(), event.topic.clone()))
+            .and_then(|listeners| listeners.downcast_ref::<TopicListeners<T>>())
+        {
+            Some(listeners) => listeners.values().cloned().collect(),
+            None => {
+                trace!("no listener of topic [{}]", event.topic);
+                return;
+            }
+        };
+        for listener in listeners {
+            if let Err(e) = listener.handle(event) {
+                error!(
+                    "listener of topic [{}] failed to process event: {}",
+                    event.topic, e
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! The `no_std` core of comet-eventbus
+//!
+//! It provides the `Event`, `Listener` and `ListenerError` types shared with `comet-eventbus`,
+//! and a synchronous eventbus on top of `alloc` only, for embedded targets. The `Eventbus`,
+//! `Topic` and `EventListener` of `comet-eventbus` are distinct types, as they hold the
+//! registry of async and blocking listeners.
+//!
+//! ## Example
+//!
+//! ```
+//! use comet_eventbus_core::{Event, Eventbus, Listener, ListenerError};
+//!
+//! struct Blink;
+//!
+//! impl Listener<u8> for Blink {
+//!     fn handle(&self, event: &Event<u8>) -> Result<(), ListenerError> {
+//!         // toggle led `**event`
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let eventbus = Eventbus::new();
+//! eventbus.register("led", Blink);
+//! eventbus.create_topic("led").post_message(1u8);
+//! ```
+#![no_std]
+#![deny(missing_docs)]
+#![warn(
+    missing_debug_implementations,
+    single_use_lifetimes,
+    unreachable_pub,
+    future_incompatible,
+    rust_2021_compatibility
+)]
+
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+#[macro_use]
+extern crate log;
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::any::{Any, TypeId};
+use core::error::Error;
+use core::fmt::{Debug, Display, Formatter};
+use core::time::Duration;
+use spin::Mutex;
+
+mod event;
+mod event_listener;
+mod id_source;
+mod impl_sync;
+#[cfg(test)]
+mod tests;
+mod topic;
+mod topic_key;
+
+pub use event::Event;
+pub use event_listener::EventListener;
+#[cfg(feature = "std")]
+pub use id_source::RandomIds;
+pub use id_source::{IdSource, SequentialIds};
+pub use impl_sync::Listener;
+pub use topic::Topic;
+pub use topic_key::TopicKey;
+
+/// A synchronous `Eventbus` to interact with
+///
+/// Listeners are called on the posting context, which should not be an interrupt handler if
+/// the eventbus is also used elsewhere, as its spin locks would deadlock.
+#[derive(Clone)]
+pub struct Eventbus {
+    inner: Arc<EventbusInner>,
+}
+
+struct EventbusInner {
+    /// listeners of every message type and topic, by type id of the message
+    listeners: Mutex<BTreeMap<(TypeId, TopicKey), Box<dyn Any + Send>>>,
+    ids: Box<dyn IdSource>,
+}
+
+/// Error of Listener exceptions
+///
+/// Errors are either retryable, processing the event again may succeed, or fatal,
+/// see `ListenerError::is_retryable`.
+///
+/// ## Example:
+/// ```
+/// use comet_eventbus_core::{ListenerError, ListenerResultExt};
+///
+/// fn parse(input: &str) -> Result<u64, ListenerError> {
+///     if input.is_empty() {
+///         return Err(ListenerError::rejected("empty input"));
+///     }
+///     let value = input.parse::<u64>().fatal()?;
+///     Ok(value)
+/// }
+///
+/// assert!(!parse("foo").unwrap_err().is_retryable());
+/// ```
+#[derive(Debug)]
+pub enum ListenerError {
+    /// the listener did not finish in time and was cancelled
+    Timeout(Duration),
+    /// error raised by the listener
+    Custom {
+        /// the underlying error
+        source: Box<dyn Error + Send + Sync>,
+        /// whether processing the event again may succeed
+        retryable: bool,
+    },
+    /// the listener panicked
+    Panicked(String),
+    /// the listener declined the event, e.g. it is filtered out
+    Rejected(String),
+}
+
+/// Convert the error of a `Result` into `ListenerError`, so that it can be returned with `?`
+pub trait ListenerResultExt<T> {
+    /// convert the error into a retryable `ListenerError`
+    fn retryable(self) -> Result<T, ListenerError>;
+
+    /// convert the error into a fatal `ListenerError`
+    fn fatal(self) -> Result<T, ListenerError>;
+}
+
+impl Eventbus {
+    /// create an new eventbus, with `SequentialIds` as id source
+    pub fn new() -> Self {
+        Self::with_id_source(SequentialIds::default())
+    }
+
+    /// create an new eventbus, which takes listener ids from `ids`
+    pub fn with_id_source<I: IdSource>(ids: I) -> Self {
+        Self {
+            inner: Arc::new(EventbusInner {
+                listeners: Mutex::new(BTreeMap::new()),
+                ids: Box::new(ids),
+            }),
+        }
+    }
+
+    /// generate a topic key from the id source of the eventbus
+    pub fn random_topic(&self, len: usize) -> TopicKey {
+        TopicKey::random_with(len, self.inner.ids.as_ref())
+    }
+}
+
+impl Default for Eventbus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Eventbus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Eventbus")
+            .field("topics", &self.inner.listeners.lock().len())
+            .finish()
+    }
+}
+
+impl ListenerError {
+    /// wrap an error, processing the event again may succeed
+    pub fn retryable<E: Into<Box<dyn Error + Send + Sync>>>(error: E) -> Self {
+        Self::Custom {
+            source: error.into(),
+            retryable: true,
+        }
+    }
+
+    /// wrap an error, processing the event again won't succeed
+    pub fn fatal<E: Into<Box<dyn Error + Send + Sync>>>(error: E) -> Self {
+        Self::Custom {
+            source: error.into(),
+            retryable: false,
+        }
+    }
+
+    /// decline the event
+    pub fn rejected<R: Into<String>>(reason: R) -> Self {
+        Self::Rejected(reason.into())
+    }
+
+    /// check if processing the event again may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Timeout(_) => true,
+            Self::Custom { retryable, .. } => *retryable,
+            Self::Panicked(_) | Self::Rejected(_) => false,
+        }
+    }
+}
+
+impl Display for ListenerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Timeout(timeout) => write!(f, "listener timed out after {:?}", timeout),
+            Self::Custom { source, .. } => write!(f, "{}", source),
+            Self::Panicked(message) => write!(f, "listener panicked: {}", message),
+            Self::Rejected(reason) => write!(f, "event rejected: {}", reason),
+        }
+    }
+}
+
+impl Error for ListenerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Custom { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for ListenerError {
+    fn from(error: Box<dyn Error + Send + Sync>) -> Self {
+        Self::fatal(error)
+    }
+}
+
+impl From<String> for ListenerError {
+    fn from(error: String) -> Self {
+        Self::fatal(error)
+    }
+}
+
+impl From<&str> for ListenerError {
+    fn from(error: &str) -> Self {
+        Self::fatal(error)
+    }
+}
+
+impl<T, E: Into<Box<dyn Error + Send + Sync>>> ListenerResultExt<T> for Result<T, E> {
+    fn retryable(self) -> Result<T, ListenerError> {
+        self.map_err(ListenerError::retryable)
+    }
+
+    fn fatal(self) -> Result<T, ListenerError> {
+        self.map_err(ListenerError::fatal)
+    }
+}
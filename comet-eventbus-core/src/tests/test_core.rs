@@ -0,0 +1,94 @@
+use crate::*;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+struct Counter(Arc<AtomicUsize>);
+
+impl Listener<u8> for Counter {
+    fn handle(&self, event: &Event<u8>) -> Result<(), ListenerError> {
+        if **event == 0 {
+            return Err(ListenerError::rejected("zero"));
+        }
+        self.0.fetch_add(**event as usize, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_post() {
+    let eventbus = Eventbus::new();
+    let counter = Arc::new(AtomicUsize::new(0));
+    let listener = eventbus.register("leds", Counter(counter.clone()));
+    eventbus.register("other", Counter(counter.clone()));
+    let topic = eventbus.create_topic("leds");
+    topic.post_message(2u8);
+    topic.post_message(0u8);
+    // messages of another type are not delivered
+    eventbus.post(&Event::new("leds", 1u16));
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+    listener.unregister();
+    topic.post_message(3u8);
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+}
+
+#[derive(Default)]
+struct Hooked(Arc<AtomicUsize>);
+
+impl Listener<u8> for Hooked {
+    fn handle(&self, _: &Event<u8>) -> Result<(), ListenerError> {
+        Ok(())
+    }
+
+    fn on_register(&self, _: &TopicKey, _: u64) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_unregister(&self, _: &TopicKey, _: u64) {
+        self.0.fetch_add(10, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_lifecycle_hooks() {
+    let eventbus = Eventbus::new();
+    let hooked = Hooked::default();
+    let calls = hooked.0.clone();
+    let listener = eventbus.register("leds", hooked);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    listener.unregister();
+    assert_eq!(calls.load(Ordering::SeqCst), 11);
+}
+
+struct Fixed;
+
+impl IdSource for Fixed {
+    fn next_id(&self) -> u64 {
+        0x0706050403020100
+    }
+}
+
+#[test]
+fn test_id_source() {
+    let eventbus = Eventbus::new();
+    let first = eventbus.register("leds", Counter(Default::default()));
+    let second = eventbus.register("leds", Counter(Default::default()));
+    assert_eq!((first.id(), second.id()), (1, 2));
+
+    let eventbus = Eventbus::with_id_source(Fixed);
+    assert_eq!(
+        eventbus.register("leds", Counter(Default::default())).id(),
+        0x0706050403020100
+    );
+    assert_eq!(
+        eventbus.random_topic(10),
+        TopicKey::from(alloc::vec![0, 1, 2, 3, 4, 5, 6, 7, 0, 1])
+    );
+}
+
+#[test]
+fn test_topic_key() {
+    let owned = TopicKey::from(b"leds".to_vec());
+    let shared = owned.clone();
+    assert_eq!(owned.as_ptr(), shared.as_ptr());
+    assert_eq!(owned, TopicKey::from("leds"));
+    assert!(TopicKey::from("led") < owned);
+}
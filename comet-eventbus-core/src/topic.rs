@@ -0,0 +1,46 @@
+use crate::{Event, Eventbus, TopicKey};
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+
+/// A `Topic` wrapper for a `TopicKey`
+pub struct Topic<T> {
+    pub(crate) key: TopicKey,
+    pub(crate) bus: Eventbus,
+    pub(crate) _message: PhantomData<T>,
+}
+
+impl<T: 'static> Topic<T> {
+    /// create an event from message
+    pub fn create_event(&self, message: T) -> Event<T> {
+        Event::new(self.key.clone(), message)
+    }
+
+    /// get the key of a topic
+    pub fn get_key(&self) -> &TopicKey {
+        &self.key
+    }
+
+    /// get the associated eventbus
+    pub fn get_bus(&self) -> &Eventbus {
+        &self.bus
+    }
+
+    /// shorthand for post event to eventbus
+    pub fn post(&self, event: &Event<T>) {
+        self.bus.post(event)
+    }
+
+    /// shorthand for post message to eventbus
+    pub fn post_message(&self, message: T) {
+        let event = self.create_event(message);
+        self.post(&event)
+    }
+}
+
+impl<T> Debug for Topic<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct(alloc::format!("Topic<{}>", core::any::type_name::<T>()).as_str())
+            .field("key", &self.key)
+            .finish()
+    }
+}
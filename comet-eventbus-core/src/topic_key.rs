@@ -0,0 +1,151 @@
+use crate::IdSource;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+use core::str::Utf8Error;
+
+/// Wrapper of bytes represent a `Topic`
+///
+/// ## Example:
+/// ```
+/// use comet_eventbus_core::TopicKey;
+///
+/// // create topic from str literal
+/// TopicKey::from("my awsome topic");
+///
+/// // crate topic from bytes literal
+/// TopicKey::from(&b"deafbeef"[..]);
+///
+/// // create topic from Vec<u8>
+/// TopicKey::from(vec![0xde, 0xaf, 0xbe, 0xef]);
+/// ```
+///
+/// Cloning a `TopicKey` never allocates, owned keys are shared.
+#[derive(Clone)]
+pub struct TopicKey(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Static(&'static [u8]),
+    Shared(Arc<[u8]>),
+}
+
+impl TopicKey {
+    /// check if both keys share the same bytes, which implies they are equal
+    fn ptr_eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.as_ref(), other.as_ref())
+    }
+
+    /// try parse topic key as an utf-8 str
+    pub fn try_as_str(&self) -> Result<&str, Utf8Error> {
+        core::str::from_utf8(self.as_ref())
+    }
+
+    /// Generate a random topic
+    #[cfg(feature = "std")]
+    pub fn random(len: usize) -> Self {
+        use rand::{thread_rng, RngCore};
+
+        let mut buf = vec![0; len];
+        thread_rng().fill_bytes(&mut buf);
+        Self::from(buf)
+    }
+
+    /// Generate a topic from the ids of an `IdSource`
+    pub fn random_with(len: usize, ids: &dyn IdSource) -> Self {
+        let mut buf = vec![0; len];
+        for chunk in buf.chunks_mut(8) {
+            chunk.copy_from_slice(&ids.next_id().to_le_bytes()[..chunk.len()]);
+        }
+        Self::from(buf)
+    }
+}
+
+impl AsRef<[u8]> for TopicKey {
+    fn as_ref(&self) -> &[u8] {
+        match &self.0 {
+            Repr::Static(bytes) => bytes,
+            Repr::Shared(bytes) => bytes,
+        }
+    }
+}
+
+impl Deref for TopicKey {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+impl PartialEq for TopicKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || self.as_ref() == other.as_ref()
+    }
+}
+
+impl Eq for TopicKey {}
+
+impl PartialOrd for TopicKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TopicKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_ref().cmp(other.as_ref())
+    }
+}
+
+impl Hash for TopicKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state)
+    }
+}
+
+impl From<Vec<u8>> for TopicKey {
+    fn from(value: Vec<u8>) -> Self {
+        Self(Repr::Shared(Arc::from(value)))
+    }
+}
+
+impl From<&'static Vec<u8>> for TopicKey {
+    fn from(value: &'static Vec<u8>) -> Self {
+        Self(Repr::Static(value))
+    }
+}
+
+impl From<&'static [u8]> for TopicKey {
+    fn from(value: &'static [u8]) -> Self {
+        Self(Repr::Static(value))
+    }
+}
+
+impl From<&'static str> for TopicKey {
+    fn from(value: &'static str) -> Self {
+        Self(Repr::Static(value.as_bytes()))
+    }
+}
+
+impl Display for TopicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &self.try_as_str().unwrap_or(&hex::encode(self.as_ref()))
+        )
+    }
+}
+
+impl Debug for TopicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("TopicKey")
+            .field(&self.try_as_str().unwrap_or(&hex::encode(self.as_ref())))
+            .finish()
+    }
+}
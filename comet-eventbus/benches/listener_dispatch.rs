@@ -0,0 +1,86 @@
+//! Latency and allocations of dispatching an event to a listener of a `StaticTopic`, through a
+//! boxed `Listener`, a `Listener` whose future is boxed by `async_trait`, and a `NativeListener`.
+use comet_eventbus::{async_trait, Event, Listener, ListenerError, NativeListener, StaticTopic};
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures::executor::block_on;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// counts allocations to report the allocations per post
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+struct Message;
+
+struct Boxed;
+
+#[async_trait]
+impl Listener<Message> for Boxed {
+    async fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        Ok(())
+    }
+}
+
+struct Native;
+
+impl NativeListener<Message> for Native {
+    async fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        Ok(())
+    }
+}
+
+/// allocations of a post, averaged over many posts
+fn allocations_per_post(mut post: impl FnMut()) -> f64 {
+    const POSTS: usize = 10_000;
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..POSTS {
+        post();
+    }
+    (ALLOCATIONS.load(Ordering::Relaxed) - before) as f64 / POSTS as f64
+}
+
+fn listener_dispatch(c: &mut Criterion) {
+    let mut dynamic = StaticTopic::<Message>::new("orders");
+    dynamic.register_boxed(Boxed);
+    let mut boxed = StaticTopic::<Message, Boxed>::new("orders");
+    boxed.register(Boxed);
+    let mut native = StaticTopic::<Message, Native>::new("orders");
+    native.register(Native);
+    let event = native.create_event(Message);
+    println!(
+        "allocations per post: Box<dyn Listener> {:.1}, Listener {:.1}, NativeListener {:.1}",
+        allocations_per_post(|| block_on(dynamic.post(&event)).unwrap()),
+        allocations_per_post(|| block_on(boxed.post(&event)).unwrap()),
+        allocations_per_post(|| block_on(native.post(&event)).unwrap()),
+    );
+
+    let mut group = c.benchmark_group("dispatch/listener");
+    group.bench_function("dyn", |b| {
+        b.iter(|| block_on(dynamic.post(&event)).unwrap())
+    });
+    group.bench_function("async_trait", |b| {
+        b.iter(|| block_on(boxed.post(&event)).unwrap())
+    });
+    group.bench_function("native", |b| {
+        b.iter(|| block_on(native.post(&event)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, listener_dispatch);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+//! Latency and allocations of a single post, through `Eventbus::post` which looks the topic up,
+//! and through a `Topic` handle which holds its listeners already.
+use comet_eventbus::{async_trait, Event, Eventbus, Listener, ListenerError, Topic, TopicKey};
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures::executor::block_on;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// counts allocations to report the allocations per post
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+struct Message;
+
+struct Noop;
+
+#[async_trait]
+impl Listener<Message> for Noop {
+    async fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        Ok(())
+    }
+}
+
+fn setup() -> (Eventbus, Topic<Message>) {
+    let eventbus = Eventbus::new();
+    let key = TopicKey::from(b"orders".to_vec());
+    block_on(async {
+        eventbus.register(key.clone(), Noop).await;
+        let topic = eventbus.create_topic(key).await;
+        (eventbus, topic)
+    })
+}
+
+/// allocations of a post, averaged over many posts
+fn allocations_per_post(mut post: impl FnMut()) -> f64 {
+    const POSTS: usize = 10_000;
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..POSTS {
+        post();
+    }
+    (ALLOCATIONS.load(Ordering::Relaxed) - before) as f64 / POSTS as f64
+}
+
+fn post_latency(c: &mut Criterion) {
+    let (eventbus, topic) = setup();
+    let key = TopicKey::from(b"orders".to_vec());
+    println!(
+        "allocations per post: Eventbus::post {:.1}, Topic::post_message {:.1}",
+        allocations_per_post(|| {
+            block_on(eventbus.post(&Event::new(key.clone(), Message))).unwrap()
+        }),
+        allocations_per_post(|| block_on(topic.post_message(Message)).unwrap()),
+    );
+
+    let mut group = c.benchmark_group("post/latency");
+    group.bench_function("eventbus", |b| {
+        b.iter(|| block_on(eventbus.post(&Event::new(key.clone(), Message))).unwrap())
+    });
+    group.bench_function("topic", |b| {
+        b.iter(|| block_on(topic.post_message(Message)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, post_latency);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+//! Throughput of posting from several threads at once, to one shared topic and to a topic
+//! per thread. With a contention-free registry, throughput grows with the number of threads.
+use comet_eventbus::{async_trait, Event, Eventbus, Listener, ListenerError, TopicKey};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures::executor::block_on;
+use std::time::{Duration, Instant};
+
+struct Message;
+
+struct Noop;
+
+#[async_trait]
+impl Listener<Message> for Noop {
+    async fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        Ok(())
+    }
+}
+
+/// post `iters` events from each of `threads` threads, returns the elapsed time
+fn post_from(eventbus: &Eventbus, topics: &[TopicKey], threads: usize, iters: u64) -> Duration {
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for thread in 0..threads {
+            let topic = topics[thread % topics.len()].clone();
+            scope.spawn(move || {
+                let event = Event::new(topic, Message);
+                block_on(async {
+                    for _ in 0..iters {
+                        eventbus.post(&event).await.unwrap();
+                    }
+                })
+            });
+        }
+    });
+    start.elapsed()
+}
+
+fn bench(c: &mut Criterion, name: &str, shared: bool) {
+    let mut group = c.benchmark_group(name);
+    for threads in [1, 2, 4, 8] {
+        let eventbus = Eventbus::new();
+        let topics: Vec<_> = (0..if shared { 1 } else { threads })
+            .map(|topic| TopicKey::from(format!("topic-{}", topic).into_bytes()))
+            .collect();
+        for topic in topics.iter() {
+            block_on(eventbus.register(topic.clone(), Noop));
+        }
+        group.throughput(Throughput::Elements(threads as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threads),
+            &threads,
+            |b, &threads| b.iter_custom(|iters| post_from(&eventbus, &topics, threads, iters)),
+        );
+    }
+    group.finish();
+}
+
+fn shared_topic(c: &mut Criterion) {
+    bench(c, "post/shared_topic", true);
+}
+
+fn topic_per_thread(c: &mut Criterion) {
+    bench(c, "post/topic_per_thread", false);
+}
+
+criterion_group!(benches, shared_topic, topic_per_thread);
+criterion_main!(benches);
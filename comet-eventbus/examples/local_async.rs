@@ -26,9 +26,9 @@ async fn main() {
     let handler = eventbus.register(topic.clone(), Handler).await;
     let topic = eventbus.create_topic(topic.clone()).await;
     let event = Event::new(topic.get_key().clone(), Message { id: 1 });
-    topic.post(&event).await;
+    topic.post(&event).await.unwrap();
     // this should not produce any output since we already unregister listener
     handler.unregister().await;
     let event = Event::new(topic.get_key().clone(), Message { id: 2 });
-    topic.post(&event).await;
+    topic.post(&event).await.unwrap();
 }
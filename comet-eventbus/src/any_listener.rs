@@ -0,0 +1,96 @@
+use crate::{AnyListener, Event, Eventbus, TopicKey};
+use comet_eventbus_core::{IdSource, RandomIds};
+use std::any::{type_name, Any};
+use std::fmt::{Debug, Formatter};
+
+/// type erased message, which can be shared with listeners
+type ErasedMessage = dyn Any + Send + Sync;
+
+/// Type erased view of an `Event` of any message type, received by an `AnyListener`
+pub struct AnyEvent<'a> {
+    topic: &'a TopicKey,
+    type_name: &'static str,
+    message: &'a ErasedMessage,
+}
+
+impl<'a> AnyEvent<'a> {
+    pub(crate) fn new<T: Send + Sync + 'static>(event: &'a Event<T>) -> Self {
+        Self {
+            topic: event.get_key(),
+            type_name: type_name::<T>(),
+            message: &**event,
+        }
+    }
+
+    /// get the key of the topic the event is posted to
+    pub fn topic(&self) -> &'a TopicKey {
+        self.topic
+    }
+
+    /// rust type name of the message
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// check if the message is of type `T`
+    pub fn is<T: 'static>(&self) -> bool {
+        self.message.is::<T>()
+    }
+
+    /// get the message if it is of type `T`
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&'a T> {
+        self.message.downcast_ref()
+    }
+}
+
+impl Debug for AnyEvent<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnyEvent")
+            .field("topic", &self.topic)
+            .field("type_name", &self.type_name)
+            .finish()
+    }
+}
+
+/// An `AnyEventListener` wrapper for `AnyListener`
+#[derive(Clone)]
+pub struct AnyEventListener {
+    pub(crate) topic: TopicKey,
+    pub(crate) rand_id: u64,
+    pub(crate) bus: Eventbus,
+}
+
+impl AnyEventListener {
+    pub(crate) fn new<K: Into<TopicKey>>(topic_key: K, bus: Eventbus) -> Self {
+        Self {
+            topic: topic_key.into(),
+            rand_id: RandomIds.next_id(),
+            bus,
+        }
+    }
+
+    /// get the id of the listener
+    pub fn id(&self) -> u64 {
+        self.rand_id
+    }
+
+    /// get the key of the subscribed topic
+    pub fn get_key(&self) -> &TopicKey {
+        &self.topic
+    }
+}
+
+impl Debug for AnyEventListener {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnyEventListener")
+            .field("topic", &self.topic)
+            .field("rand_id", &self.rand_id)
+            .finish()
+    }
+}
+
+impl Debug for dyn AnyListener {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AnyListener")
+    }
+}
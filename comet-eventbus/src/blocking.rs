@@ -0,0 +1,243 @@
+use crate::impl_async::Delivery;
+use crate::{Event, EventListener, Eventbus, Listener, ListenerError, PostError, TopicKey};
+use crate::{OverflowPolicy, ShutdownError, Topic, TopicInfo};
+use async_trait::async_trait;
+use std::future::Future;
+use std::hash::Hash;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+/// Blocking event listener, which can be registered along with async `Listener`s
+///
+/// It is the `Listener` of `comet-eventbus-core`. It may post events with
+/// `Eventbus::post_blocking` from its handler.
+pub use comet_eventbus_core::Listener as SyncListener;
+
+/// Adapter of a `SyncListener` to `Listener`
+pub(crate) struct BlockingListener<L>(pub(crate) L);
+
+#[async_trait]
+impl<T: Send + Sync + 'static, L: SyncListener<T>> Listener<T> for BlockingListener<L> {
+    async fn handle(&self, event: &Event<T>) -> Result<(), ListenerError> {
+        self.0.handle(event)
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.0.name()
+    }
+
+    async fn on_register(&self, topic: &TopicKey, listener_id: u64) {
+        self.0.on_register(topic, listener_id)
+    }
+
+    async fn on_unregister(&self, topic: &TopicKey, listener_id: u64) {
+        self.0.on_unregister(topic, listener_id)
+    }
+
+    async fn on_bus_shutdown(&self, topic: &TopicKey, listener_id: u64) {
+        self.0.on_bus_shutdown(topic, listener_id)
+    }
+}
+
+/// how the listeners of blocking posts are called
+#[cfg(not(feature = "sync_parallel"))]
+const DELIVERY: Delivery = Delivery::Concurrent;
+#[cfg(feature = "sync_parallel")]
+const DELIVERY: Delivery = Delivery::Parallel;
+
+impl Eventbus {
+    /// register a blocking listener to eventbus
+    pub async fn register_sync<T: Send + Sync + 'static, K: Into<TopicKey>, L: SyncListener<T>>(
+        &self,
+        topic_key: K,
+        listener: L,
+    ) -> EventListener<T> {
+        self.register(topic_key, BlockingListener(listener)).await
+    }
+
+    /// register a blocking listener to eventbus from non-async code
+    ///
+    /// Must not be called from an async context, as it blocks the current thread.
+    pub fn register_blocking<T: Send + Sync + 'static, K: Into<TopicKey>, L: SyncListener<T>>(
+        &self,
+        topic_key: K,
+        listener: L,
+    ) -> EventListener<T> {
+        block_on(self.register_sync(topic_key, listener))
+    }
+
+    /// register a blocking listener to a consumer group of a topic from non-async code
+    ///
+    /// Must not be called from an async context, as it blocks the current thread.
+    pub fn register_group_blocking<
+        T: Send + Sync + 'static,
+        K: Into<TopicKey>,
+        G: Into<String>,
+        L: SyncListener<T>,
+    >(
+        &self,
+        topic_key: K,
+        group: G,
+        listener: L,
+    ) -> EventListener<T> {
+        block_on(self.register_group(topic_key, group, BlockingListener(listener)))
+    }
+
+    /// create a `Topic` using a topic key from non-async code
+    ///
+    /// Must not be called from an async context, as it blocks the current thread.
+    pub fn create_topic_blocking<T: 'static, K: Into<TopicKey>>(&self, topic_key: K) -> Topic<T> {
+        block_on(self.create_topic(topic_key))
+    }
+
+    /// deliver events of a topic in order per partition key, from non-async code
+    ///
+    /// Must not be called from an async context, as it blocks the current thread.
+    pub fn set_partition_key_blocking<
+        T: 'static,
+        K: Into<TopicKey>,
+        P: Hash,
+        F: Fn(&T) -> P + Send + Sync + 'static,
+    >(
+        &self,
+        topic_key: K,
+        extractor: F,
+    ) {
+        block_on(self.set_partition_key(topic_key, extractor))
+    }
+
+    /// deliver events of a topic through a bounded mailbox, from non-async code
+    ///
+    /// Must not be called from an async context, as it blocks the current thread.
+    pub fn enable_queue_blocking<T: Clone + Send + Sync + 'static, K: Into<TopicKey>>(
+        &self,
+        topic_key: K,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) {
+        block_on(self.enable_queue::<T, _>(topic_key, capacity, policy))
+    }
+
+    /// deliver events of a topic by spawning a task per listener, from non-async code
+    ///
+    /// Must not be called from an async context, as it blocks the current thread.
+    pub fn enable_parallel_blocking<T: Clone + Send + Sync + 'static, K: Into<TopicKey>>(
+        &self,
+        topic_key: K,
+        max_tasks: Option<usize>,
+    ) {
+        block_on(self.enable_parallel::<T, _>(topic_key, max_tasks))
+    }
+
+    /// describe every topic and its listeners from non-async code
+    ///
+    /// Must not be called from an async context, as it blocks the current thread.
+    pub fn topics_blocking(&self) -> Vec<TopicInfo> {
+        block_on(self.topics())
+    }
+
+    /// shut the eventbus down from non-async code
+    ///
+    /// Must not be called from an async context, as it blocks the current thread.
+    ///
+    /// # Errors
+    /// Shutting down fails in the same cases as `Eventbus::shutdown`.
+    pub fn shutdown_blocking(&self, timeout: Duration) -> Result<(), ShutdownError> {
+        block_on(self.shutdown(timeout))
+    }
+
+    /// post an event to eventbus from non-async code, blocks until it is delivered to
+    /// async and blocking listeners
+    ///
+    /// Listeners are called on the current thread, or in parallel on the rayon thread pool
+    /// with the `sync_parallel` feature. Tasks of queued and parallel topics are spawned on
+    /// the `Runtime` of the eventbus. Must not be called from an async context, as it blocks
+    /// the current thread.
+    ///
+    /// # Errors
+    /// Posting fails in the same cases as `Eventbus::post`.
+    pub fn post_blocking<T: Send + Sync + 'static>(
+        &self,
+        event: &Event<T>,
+    ) -> Result<(), PostError> {
+        trace!("recv blocking post [{:?}]", event.get_key());
+        block_on(self.inner.topic_handlers.post(None, event, DELIVERY))
+    }
+}
+
+impl<T: Send + Sync + 'static> Topic<T> {
+    /// post an event to the listeners of the topic from non-async code
+    ///
+    /// Listeners are called like with `Eventbus::post_blocking`. Must not be called from an
+    /// async context, as it blocks the current thread.
+    ///
+    /// # Errors
+    /// Posting fails in the same cases as `Topic::post`.
+    pub fn post_blocking(&self, event: &Event<T>) -> Result<(), PostError> {
+        if *event.get_key() != self.key {
+            return self.bus.post_blocking(event);
+        }
+        trace!("recv blocking post [{:?}]", event.get_key());
+        let topic_handlers = &self.bus.inner.topic_handlers;
+        block_on(topic_handlers.post(Some(&self.event_listeners), event, DELIVERY))
+    }
+
+    /// shorthand for post message to the topic from non-async code
+    pub fn post_message_blocking(&self, message: T) -> Result<(), PostError> {
+        let event = self.create_event(message);
+        self.post_blocking(&event)
+    }
+}
+
+impl<T: 'static> EventListener<T> {
+    /// unregister listener from eventbus from non-async code
+    ///
+    /// Must not be called from an async context, as it blocks the current thread.
+    pub fn unregister_blocking(self) {
+        block_on(self.unregister())
+    }
+}
+
+/// Wakes the thread blocked on a future
+struct ThreadWaker {
+    thread: Thread,
+    woken: AtomicBool,
+}
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.thread.unpark();
+    }
+}
+
+/// run a future to completion on the current thread
+///
+/// Unlike `futures::executor::block_on`, it can be nested, so that blocking listeners can post
+/// from their handler. Each call waits for its own wake-ups, as nested calls may consume the
+/// unpark token of the thread.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Arc::new(ThreadWaker {
+        thread: thread::current(),
+        woken: AtomicBool::new(false),
+    });
+    let thread_waker = Waker::from(waker.clone());
+    let mut cx = Context::from_waker(&thread_waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        while !waker.woken.swap(false, Ordering::Acquire) {
+            thread::park();
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::topic::Topic;
-use crate::{Event, EventListener, Eventbus, Listener, ListenerError, TopicKey};
+use crate::{instrument, spans};
+use crate::{Event, EventListener, Eventbus, Listener, ListenerError, PostError, TopicKey};
 use bridge::bridger_server::{Bridger, BridgerServer};
 use bridge::PostReq;
 use serde::{de::DeserializeOwned, Serialize};
@@ -43,6 +44,12 @@ pub struct BridgeListener<T> {
 }
 
 /// A bridge to connect two seperated `Eventbus`
+///
+/// With the `otel` feature, the trace context of the posting span is sent along with the
+/// event, and the receiving eventbus delivers it in a child span. The context is encoded and
+/// decoded by the global propagator of `opentelemetry`, which does nothing by default, so
+/// applications on both sides need to install one, e.g.
+/// `opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new())`.
 #[derive(Debug, Clone)]
 #[cfg_attr(docsrs, doc(cfg(feature = "bridge")))]
 pub struct EventbusBridge {
@@ -71,6 +78,19 @@ pub enum BridgeError {
     Deserialization(bincode::Error),
 }
 
+/// Error of posting an event through the bridge
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(docsrs, doc(cfg(feature = "bridge")))]
+pub enum BridgePostError {
+    /// the local eventbus rejected the event, it is not forwarded to connected eventbus
+    #[error(transparent)]
+    Rejected(#[from] PostError),
+    /// some connected eventbus failed to receive the event, their clients are returned for
+    /// retrying
+    #[error("failed to forward event to {} connected eventbus", .0.len())]
+    Forward(Vec<BridgerClient>),
+}
+
 impl<T> BridgedTopic<T> {
     /// get topic key
     pub fn get_key(&self) -> &TopicKey {
@@ -80,7 +100,7 @@ impl<T> BridgedTopic<T> {
 
 impl<T: Serialize + Send + Sync + 'static> BridgedTopic<T> {
     /// shorthand for post event to eventbus
-    pub async fn post(&self, event: &Event<T>) -> Result<(), Vec<BridgerClient>> {
+    pub async fn post(&self, event: &Event<T>) -> Result<(), BridgePostError> {
         self.bus.post(event).await
     }
 }
@@ -91,47 +111,63 @@ impl SerializedMessage {
     }
 }
 
-impl<T: Serialize> Event<T> {
+/// Serialize an `Event` to send it through the bridge
+#[cfg_attr(docsrs, doc(cfg(feature = "bridge")))]
+pub trait SerializeEvent {
     /// serialize a message
-    pub fn serialized(&self) -> Result<Event<SerializedMessage>, BridgeError> {
-        let serialized = bincode::serialize(&self.message).map_err(BridgeError::Serialization)?;
-        Ok(Event {
-            topic: self.topic.clone(),
-            message: SerializedMessage::new(serialized),
-        })
-    }
+    fn serialized(&self) -> Result<Event<SerializedMessage>, BridgeError>;
 }
 
-impl Event<SerializedMessage> {
+/// Downcast a serialized `Event` received from the bridge
+#[cfg_attr(docsrs, doc(cfg(feature = "bridge")))]
+pub trait DowncastEvent {
     /// downcast a Serialized Event to a concreate type.
-    pub fn downcast<T: Sized + DeserializeOwned + 'static>(&self) -> Result<Event<T>, BridgeError> {
+    fn downcast<T: Sized + DeserializeOwned + 'static>(&self) -> Result<Event<T>, BridgeError>;
+}
+
+impl<T: Serialize> SerializeEvent for Event<T> {
+    fn serialized(&self) -> Result<Event<SerializedMessage>, BridgeError> {
+        let serialized = bincode::serialize(&**self).map_err(BridgeError::Serialization)?;
+        Ok(Event::new(
+            self.get_key().clone(),
+            SerializedMessage::new(serialized),
+        ))
+    }
+}
+
+impl DowncastEvent for Event<SerializedMessage> {
+    fn downcast<T: Sized + DeserializeOwned + 'static>(&self) -> Result<Event<T>, BridgeError> {
         let message =
-            bincode::deserialize::<T>(&self.message.inner).map_err(BridgeError::Deserialization)?;
-        Ok(Event {
-            topic: self.topic.clone(),
-            message,
-        })
+            bincode::deserialize::<T>(&self.inner).map_err(BridgeError::Deserialization)?;
+        Ok(Event::new(self.get_key().clone(), message))
     }
 }
 
 impl From<PostReq> for Event<SerializedMessage> {
     fn from(req: PostReq) -> Self {
-        Event {
-            topic: TopicKey::from(req.topic),
-            message: SerializedMessage::new(req.message),
-        }
+        Event::new(
+            TopicKey::from(req.topic),
+            SerializedMessage::new(req.message),
+        )
     }
 }
 
 impl From<Event<SerializedMessage>> for PostReq {
     fn from(event: Event<SerializedMessage>) -> Self {
         PostReq {
-            topic: event.topic.as_ref().to_vec(),
-            message: event.message.inner,
+            topic: event.get_key().as_ref().to_vec(),
+            message: event.into_inner().inner,
+            metadata: Default::default(),
         }
     }
 }
 
+impl From<BridgeError> for ListenerError {
+    fn from(error: BridgeError) -> Self {
+        Self::fatal(error)
+    }
+}
+
 impl<T> BridgeListener<T> {
     fn new<L: Listener<T>>(listener: L) -> Self {
         Self {
@@ -145,19 +181,40 @@ impl<T: Sized + DeserializeOwned + Send + Sync + 'static> Listener<SerializedMes
     for BridgeListener<T>
 {
     async fn handle(&self, event: &Event<SerializedMessage>) -> Result<(), ListenerError> {
-        trace!("handle serialized event of [{:?}]", event.topic);
+        trace!("handle serialized event of [{:?}]", event.get_key());
         let event = event.downcast::<T>()?;
         self.inner.handle(&event).await
     }
+
+    fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+
+    async fn on_register(&self, topic: &TopicKey, listener_id: u64) {
+        self.inner.on_register(topic, listener_id).await
+    }
+
+    async fn on_unregister(&self, topic: &TopicKey, listener_id: u64) {
+        self.inner.on_unregister(topic, listener_id).await
+    }
+
+    async fn on_bus_shutdown(&self, topic: &TopicKey, listener_id: u64) {
+        self.inner.on_bus_shutdown(topic, listener_id).await
+    }
 }
 
 #[tonic::async_trait]
 impl Bridger for EventbusBridge {
     async fn post(&self, request: Request<PostReq>) -> Result<Response<()>, Status> {
         trace!("recv event from grpc: {:?}", request);
-        let req = request.into_inner();
+        let mut req = request.into_inner();
+        let metadata = std::mem::take(&mut req.metadata);
         let event = Event::from(req);
-        self.bus.post(&event).await;
+        instrument::bridge_received(event.get_key());
+        let span = spans::received(event.get_key(), &metadata);
+        spans::instrument(self.bus.post(&event), span)
+            .await
+            .map_err(|e| Status::unavailable(e.to_string()))?;
         Ok(Response::new(()))
     }
 }
@@ -186,6 +243,11 @@ impl EventbusBridge {
         }
     }
 
+    /// get the bridged eventbus
+    pub fn get_bus(&self) -> &Eventbus {
+        &self.bus
+    }
+
     /// connect to another Eventbus
     pub async fn connect<E: AsRef<str>>(&self, endpoint: E) -> Result<(), tonic::transport::Error> {
         let endpoint = endpoint.as_ref().to_string();
@@ -194,11 +256,13 @@ impl EventbusBridge {
         Ok(())
     }
 
-    /// bind to an address and listen for connections
+    /// bind to an address and listen for connections,
+    /// the server stops once the bridged `Eventbus` shuts down
     pub async fn listen(self, addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+        let bus = self.bus.clone();
         Server::builder()
             .add_service(BridgerServer::new(self))
-            .serve(addr)
+            .serve_with_shutdown(addr, async move { bus.wait_for_shutdown().await })
             .await
     }
 
@@ -243,15 +307,21 @@ impl EventbusBridge {
     /// This method panics if `T` cannot be successfully serialized.
     ///
     /// # Errors
-    /// This method failed if any of send task to the connected eventbus failed.
-    /// A `Vec` of `BridgerClient` is returned for retrying.
+    /// This method fails without forwarding the event if the local eventbus rejects it,
+    /// e.g. once it is shut down. It also fails if sending to any connected eventbus failed,
+    /// their `BridgerClient`s are returned for retrying.
     pub async fn post<T: Serialize + Send + Sync + 'static>(
         &self,
         event: &Event<T>,
-    ) -> Result<(), Vec<BridgerClient>> {
-        let serialized: PostReq = event.serialized().unwrap().into();
+    ) -> Result<(), BridgePostError> {
+        #[cfg_attr(not(feature = "otel"), allow(unused_mut))]
+        let mut serialized: PostReq = event.serialized().unwrap().into();
+        #[cfg(feature = "otel")]
+        {
+            serialized.metadata = spans::inject();
+        }
         let mut guard = self.clients.lock().await;
-        self.bus.post(event).await;
+        self.bus.post(event).await?;
 
         let failed_clients: Vec<BridgerClient> = futures::future::join_all(
             guard
@@ -264,11 +334,12 @@ impl EventbusBridge {
         .filter(|(result, _)| result.is_err())
         .map(|(_, (_, client))| client.clone())
         .collect();
+        instrument::bridge_sent(event.get_key(), guard.len() - failed_clients.len());
 
         if failed_clients.is_empty() {
             Ok(())
         } else {
-            Err(failed_clients)
+            Err(BridgePostError::Forward(failed_clients))
         }
     }
 }
@@ -0,0 +1,184 @@
+use crate::{Event, Listener};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Strategy to pick the member of a consumer group which handles an event
+#[derive(Default)]
+pub enum GroupStrategy<T> {
+    /// pick members in turn
+    #[default]
+    RoundRobin,
+    /// pick the member with the fewest in-flight events
+    LeastBusy,
+    /// pick the member by the hash of a key extracted from the message
+    KeyHash(Arc<dyn Fn(&T) -> u64 + Send + Sync>),
+}
+
+pub(crate) struct ConsumerGroup<T> {
+    strategy: GroupStrategy<T>,
+    members: Vec<GroupMember<T>>,
+    cursor: AtomicUsize,
+}
+
+pub(crate) struct GroupMember<T> {
+    pub(crate) rand_id: u64,
+    pub(crate) listener: Arc<dyn Listener<T>>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+pub(crate) struct InFlightGuard(Arc<AtomicUsize>);
+
+/// hashed key extracted from a message
+pub(crate) type KeyFn<T> = Arc<dyn Fn(&T) -> u64 + Send + Sync>;
+
+/// wrap a key extractor to produce the hash of the key
+pub(crate) fn key_hasher<T, K: Hash, F: Fn(&T) -> K + Send + Sync + 'static>(
+    extractor: F,
+) -> KeyFn<T> {
+    Arc::new(move |message| {
+        let mut hasher = DefaultHasher::new();
+        extractor(message).hash(&mut hasher);
+        hasher.finish()
+    })
+}
+
+impl<T> GroupStrategy<T> {
+    /// create a `KeyHash` strategy from a key extractor
+    pub fn key_hash<K: Hash, F: Fn(&T) -> K + Send + Sync + 'static>(extractor: F) -> Self {
+        Self::KeyHash(key_hasher(extractor))
+    }
+}
+
+impl<T> Clone for GroupStrategy<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::RoundRobin => Self::RoundRobin,
+            Self::LeastBusy => Self::LeastBusy,
+            Self::KeyHash(extractor) => Self::KeyHash(extractor.clone()),
+        }
+    }
+}
+
+impl<T> Debug for GroupStrategy<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RoundRobin => f.write_str("RoundRobin"),
+            Self::LeastBusy => f.write_str("LeastBusy"),
+            Self::KeyHash(_) => f.write_str("KeyHash"),
+        }
+    }
+}
+
+impl<T> ConsumerGroup<T> {
+    pub(crate) fn new(strategy: GroupStrategy<T>) -> Self {
+        Self {
+            strategy,
+            members: Vec::new(),
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn set_strategy(&mut self, strategy: GroupStrategy<T>) {
+        self.strategy = strategy;
+    }
+
+    pub(crate) fn insert(&mut self, rand_id: u64, listener: Arc<dyn Listener<T>>) {
+        self.members.push(GroupMember {
+            rand_id,
+            listener,
+            in_flight: Default::default(),
+        });
+    }
+
+    pub(crate) fn remove(&mut self, rand_id: u64) -> Option<Arc<dyn Listener<T>>> {
+        let idx = self
+            .members
+            .iter()
+            .position(|member| member.rand_id == rand_id)?;
+        Some(self.members.remove(idx).listener)
+    }
+
+    pub(crate) fn members(&self) -> impl Iterator<Item = (u64, Arc<dyn Listener<T>>)> + '_ {
+        self.members
+            .iter()
+            .map(|member| (member.rand_id, member.listener.clone()))
+    }
+
+    pub(crate) fn contains(&self, rand_id: u64) -> bool {
+        self.members.iter().any(|member| member.rand_id == rand_id)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub(crate) fn has_default_strategy(&self) -> bool {
+        matches!(self.strategy, GroupStrategy::RoundRobin)
+    }
+
+    /// members in the order they should be tried for an event,
+    /// the chosen one comes first, the rest are used for re-dispatching
+    pub(crate) fn candidates(&self, event: &Event<T>) -> Vec<GroupMember<T>> {
+        let len = self.members.len();
+        if len == 0 {
+            return Vec::new();
+        }
+        let start = match &self.strategy {
+            GroupStrategy::RoundRobin => self.cursor.fetch_add(1, Ordering::Relaxed) % len,
+            GroupStrategy::LeastBusy => self
+                .members
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, member)| member.in_flight.load(Ordering::Relaxed))
+                .map(|(idx, _)| idx)
+                .unwrap_or_default(),
+            GroupStrategy::KeyHash(extractor) => (extractor(&**event) % len as u64) as usize,
+        };
+        (0..len)
+            .map(|offset| self.members[(start + offset) % len].clone())
+            .collect()
+    }
+}
+
+impl<T> Debug for ConsumerGroup<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConsumerGroup")
+            .field("strategy", &self.strategy)
+            .field(
+                "members",
+                &self
+                    .members
+                    .iter()
+                    .map(|member| member.rand_id)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<T> GroupMember<T> {
+    /// mark the member as busy until the returned guard is dropped
+    pub(crate) fn begin(&self) -> InFlightGuard {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard(self.in_flight.clone())
+    }
+}
+
+impl<T> Clone for GroupMember<T> {
+    fn clone(&self) -> Self {
+        Self {
+            rand_id: self.rand_id,
+            listener: self.listener.clone(),
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
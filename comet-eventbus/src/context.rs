@@ -0,0 +1,53 @@
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// Context of a single listener invocation
+///
+/// The cancellation token is cancelled when the listener is unregistered, when the invocation
+/// times out or when the eventbus shuts down. Long running handlers should check it and stop
+/// cooperatively.
+#[derive(Debug, Clone)]
+pub struct ListenerContext {
+    listener_id: u64,
+    token: CancellationToken,
+    deadline: Option<Instant>,
+}
+
+impl ListenerContext {
+    pub(crate) fn new(
+        listener_id: u64,
+        token: CancellationToken,
+        deadline: Option<Instant>,
+    ) -> Self {
+        Self {
+            listener_id,
+            token,
+            deadline,
+        }
+    }
+
+    /// id of the invoked listener, same as `EventListener::id`
+    pub fn listener_id(&self) -> u64 {
+        self.listener_id
+    }
+
+    /// the instant by which the invocation must finish, if it has a timeout
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// the cancellation token of this invocation
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// check if the invocation is cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// wait until the invocation is cancelled
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{ListenerError, TopicKey};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, RwLock};
+
+/// Handler of listener failures, installed on an `Eventbus` globally or per topic
+///
+/// It is called synchronously on the delivering task, once per failed listener,
+/// so slow work like alerting should be handed off.
+///
+/// Closures of `Fn(&ListenerFailure<'_>)` are handlers as well.
+pub trait ErrorHandler: Send + Sync + 'static {
+    /// called when a listener fails to process an event
+    fn on_error(&self, failure: &ListenerFailure<'_>);
+}
+
+impl<F: Fn(&ListenerFailure<'_>) + Send + Sync + 'static> ErrorHandler for F {
+    fn on_error(&self, failure: &ListenerFailure<'_>) {
+        self(failure)
+    }
+}
+
+/// A listener failure seen by an `ErrorHandler`
+#[derive(Debug)]
+pub struct ListenerFailure<'a> {
+    /// key of the topic
+    pub topic: &'a TopicKey,
+    /// rust type name of the message, or of the erased event for an `AnyListener`
+    pub type_name: &'static str,
+    /// id of the failed listener
+    pub listener_id: u64,
+    /// consumer group of the listener, if any
+    pub group: Option<&'a str>,
+    /// the error returned by the listener
+    pub error: &'a ListenerError,
+}
+
+/// Error handlers of an `Eventbus`, failures are logged when none applies
+#[derive(Default)]
+pub(crate) struct ErrorHandlers {
+    global: RwLock<Option<Arc<dyn ErrorHandler>>>,
+    topics: RwLock<HashMap<TopicKey, Arc<dyn ErrorHandler>>>,
+}
+
+impl ErrorHandlers {
+    pub(crate) fn set(&self, handler: Option<Arc<dyn ErrorHandler>>) {
+        *self.global.write().unwrap() = handler;
+    }
+
+    pub(crate) fn set_topic(&self, topic: TopicKey, handler: Option<Arc<dyn ErrorHandler>>) {
+        let mut topics = self.topics.write().unwrap();
+        match handler {
+            Some(handler) => topics.insert(topic, handler),
+            None => topics.remove(&topic),
+        };
+    }
+
+    /// route a failure to the handler of its topic, else to the global one
+    pub(crate) fn report(&self, failure: &ListenerFailure<'_>) {
+        let handler = self
+            .topics
+            .read()
+            .unwrap()
+            .get(failure.topic)
+            .cloned()
+            .or_else(|| self.global.read().unwrap().clone());
+        match (handler, failure.group) {
+            (Some(handler), _) => handler.on_error(failure),
+            (None, Some(group)) => error!(
+                "member {} of group [{}] of topic [{}] failed to process event: {:?}",
+                failure.listener_id, group, failure.topic, failure.error
+            ),
+            (None, None) => error!(
+                "listener {} of topic [{}] failed to process event: {:?}",
+                failure.listener_id, failure.topic, failure.error
+            ),
+        }
+    }
+}
+
+impl Debug for ErrorHandlers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorHandlers")
+            .field("global", &self.global.read().unwrap().is_some())
+            .field(
+                "topics",
+                &self.topics.read().unwrap().keys().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
@@ -1,14 +1,17 @@
 use crate::{Eventbus, TopicKey};
-use rand::{thread_rng, RngCore};
+use comet_eventbus_core::{IdSource, RandomIds};
 use std::fmt::{Debug, Formatter};
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
+use tokio_util::sync::CancellationToken;
 
 /// An `EventListener` wrapper for `Listener`
 pub struct EventListener<T> {
     pub(crate) topic: TopicKey,
     pub(crate) rand_id: u64,
     pub(crate) bus: Eventbus,
+    /// cancelled once the listener is unregistered
+    pub(crate) cancel: CancellationToken,
     _handler: PhantomData<T>,
 }
 
@@ -16,11 +19,22 @@ impl<T> EventListener<T> {
     pub(crate) fn new<K: Into<TopicKey>>(topic_key: K, bus: Eventbus) -> EventListener<T> {
         EventListener {
             topic: topic_key.into(),
-            rand_id: thread_rng().next_u64(),
+            rand_id: RandomIds.next_id(),
+            cancel: bus.inner.cancel.child_token(),
             bus,
             _handler: PhantomData,
         }
     }
+
+    /// get the id of the listener
+    pub fn id(&self) -> u64 {
+        self.rand_id
+    }
+
+    /// get the key of the subscribed topic
+    pub fn get_key(&self) -> &TopicKey {
+        &self.topic
+    }
 }
 
 impl<T> PartialEq<Self> for EventListener<T> {
@@ -44,6 +58,7 @@ impl<T> Clone for EventListener<T> {
             topic: self.topic.clone(),
             rand_id: self.rand_id,
             bus: self.bus.clone(),
+            cancel: self.cancel.clone(),
             _handler: PhantomData,
         }
     }
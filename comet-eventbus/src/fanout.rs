@@ -0,0 +1,151 @@
+use crate::spans::{self, Span};
+use crate::{Event, Listener, TopicHandlers};
+use futures::channel::oneshot;
+use futures::future;
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::mem;
+use std::pin::pin;
+use std::sync::{Arc, Weak};
+use tokio::sync::Semaphore;
+
+thread_local! {
+    /// permits held by the fanout task being polled on this thread and by the posts awaiting it,
+    /// by address of their semaphore
+    static HELD: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Deliver events of a topic by spawning a task per listener, so that they run in parallel
+///
+/// The event is cloned once and shared by the tasks. The post waits for every task, so
+/// failures are reported and counted as when listeners are polled on the posting task.
+///
+/// A task acquires a permit of a bounded topic before calling its listener. If the listener
+/// posts to a topic whose permits are held by its task or the tasks awaiting it, the event is
+/// delivered on its task instead, as new tasks would wait for those permits forever.
+pub(crate) struct Fanout<T> {
+    clone: fn(&Event<T>) -> Event<T>,
+    /// bounds the number of running tasks of the topic, unbounded if `None`
+    permits: Option<Arc<Semaphore>>,
+    topic_handlers: Weak<TopicHandlers>,
+}
+
+impl<T: Send + Sync + 'static> Fanout<T> {
+    pub(crate) fn new(
+        clone: fn(&Event<T>) -> Event<T>,
+        max_tasks: Option<usize>,
+        topic_handlers: Weak<TopicHandlers>,
+    ) -> Self {
+        Self {
+            clone,
+            permits: max_tasks.map(|max_tasks| Arc::new(Semaphore::new(max_tasks.max(1)))),
+            topic_handlers,
+        }
+    }
+
+    /// deliver an event to plain listeners, returns the number of failures
+    pub(crate) async fn deliver(
+        &self,
+        topic_handlers: &TopicHandlers,
+        listeners: &[(u64, Arc<dyn Listener<T>>)],
+        event: &Event<T>,
+    ) -> usize {
+        if listeners.is_empty() {
+            return 0;
+        }
+        let mut held = HELD.with(|held| held.borrow().clone());
+        if let Some(permits) = &self.permits {
+            let id = Arc::as_ptr(permits) as usize;
+            if held.contains(&id) {
+                return future::join_all(listeners.iter().map(|(rand_id, listener)| {
+                    topic_handlers.notify_listener(*rand_id, listener, event)
+                }))
+                .await
+                .into_iter()
+                .filter(|ok| !ok)
+                .count();
+            }
+            held.push(id);
+        }
+        let event = Arc::new((self.clone)(event));
+        let runtime = topic_handlers.runtime.get();
+        let mut results = Vec::with_capacity(listeners.len());
+        for (rand_id, listener) in listeners {
+            let (tx, rx) = oneshot::channel();
+            let (rand_id, listener, event) = (*rand_id, listener.clone(), event.clone());
+            let (topic_handlers, permits) = (self.topic_handlers.clone(), self.permits.clone());
+            let mut held = held.clone();
+            let task = async move {
+                let _permit = match permits {
+                    Some(permits) => Some(
+                        permits
+                            .acquire_owned()
+                            .await
+                            .expect("permits are never closed"),
+                    ),
+                    None => None,
+                };
+                let Some(topic_handlers) = topic_handlers.upgrade() else {
+                    return;
+                };
+                let ok = holding(
+                    &mut held,
+                    topic_handlers.notify_listener(rand_id, &listener, &event),
+                )
+                .await;
+                let _ = tx.send(ok);
+            };
+            // delivered in the span of the post
+            runtime.spawn(Box::pin(spans::instrument(task, Span::current())));
+            results.push(rx);
+        }
+        // a task dropped by the runtime before it finished counts as a failure
+        future::join_all(results)
+            .await
+            .into_iter()
+            .filter(|result| !matches!(result, Ok(true)))
+            .count()
+    }
+}
+
+/// poll a future with `held` as the permits held on this thread
+async fn holding<F: Future>(held: &mut Vec<usize>, future: F) -> F::Output {
+    /// puts the permits of the thread back, even if polling panics
+    struct Swap<'a>(&'a mut Vec<usize>);
+
+    impl Drop for Swap<'_> {
+        fn drop(&mut self) {
+            HELD.with(|held| mem::swap(&mut *held.borrow_mut(), self.0));
+        }
+    }
+
+    let mut future = pin!(future);
+    future::poll_fn(|cx| {
+        HELD.with(|current| mem::swap(&mut *current.borrow_mut(), held));
+        let _swap = Swap(held);
+        future.as_mut().poll(cx)
+    })
+    .await
+}
+
+impl<T> Clone for Fanout<T> {
+    fn clone(&self) -> Self {
+        Self {
+            clone: self.clone,
+            permits: self.permits.clone(),
+            topic_handlers: self.topic_handlers.clone(),
+        }
+    }
+}
+
+impl<T> Debug for Fanout<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fanout")
+            .field(
+                "available",
+                &self.permits.as_ref().map(|p| p.available_permits()),
+            )
+            .finish()
+    }
+}
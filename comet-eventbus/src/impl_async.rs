@@ -1,142 +1,893 @@
+#[cfg(feature = "sync_parallel")]
+use crate::blocking::block_on;
+use crate::consumer_group::{key_hasher, GroupMember};
+use crate::error_handler::ErrorHandlers;
+use crate::fanout::Fanout;
+use crate::listener_options::{ManagedListener, PlainListener};
+use crate::mailbox::Mailbox;
+use crate::partition::Partitioner;
+use crate::registry::AnyTopicListeners;
+use crate::{instrument, runtime, spans};
 use crate::{
-    Event, EventListener, EventListeners, Eventbus, ListenerError, Topic, TopicHandlers,
-    TopicHandlersMap, TopicKey,
+    listener_panicked, AnyEvent, AnyEventListener, Event, EventListener, EventListeners, Eventbus,
+    GroupStrategy, ListenerContext, ListenerError, ListenerFailure, ListenerOptions,
+    OverflowPolicy, PostError, PostOutcome, Runtime, ShutdownError, Topic, TopicHandlers,
+    TopicInfo, TopicKey,
 };
 use async_trait::async_trait;
-use futures::future;
+use futures::{future, FutureExt};
+#[cfg(feature = "sync_parallel")]
+use rayon::prelude::*;
+use std::any::type_name;
+use std::hash::Hash;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Event listener
 ///
+/// Besides handling events, a listener can hook into its lifecycle to set up and tear down
+/// resources along with the subscription.
+///
 /// Note: the struct which implements `Listener` need to be `Send` and `Sync`
 #[async_trait]
-#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
 pub trait Listener<T>: Send + Sync + 'static {
     /// handler callback to process event
     async fn handle(&self, _: &Event<T>) -> Result<(), ListenerError>;
+
+    /// human readable name of the listener, shown by `Eventbus::topics`
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// called once the listener is registered to a topic
+    async fn on_register(&self, _topic: &TopicKey, _listener_id: u64) {}
+
+    /// called once the listener is unregistered from a topic
+    async fn on_unregister(&self, _topic: &TopicKey, _listener_id: u64) {}
+
+    /// called when the eventbus shuts down, after events in flight are delivered
+    async fn on_bus_shutdown(&self, _topic: &TopicKey, _listener_id: u64) {}
+}
+
+/// Event listener which receives a `ListenerContext` alongside the event
+///
+/// Note: the struct which implements `ContextListener` need to be `Send` and `Sync`
+#[async_trait]
+pub trait ContextListener<T>: Send + Sync + 'static {
+    /// handler callback to process event
+    async fn handle(&self, _: &Event<T>, _: &ListenerContext) -> Result<(), ListenerError>;
+
+    /// human readable name of the listener, shown by `Eventbus::topics`
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// called once the listener is registered to a topic
+    async fn on_register(&self, _topic: &TopicKey, _listener_id: u64) {}
+
+    /// called once the listener is unregistered from a topic
+    async fn on_unregister(&self, _topic: &TopicKey, _listener_id: u64) {}
+
+    /// called when the eventbus shuts down, after events in flight are delivered
+    async fn on_bus_shutdown(&self, _topic: &TopicKey, _listener_id: u64) {}
+}
+
+/// Event listener of any message type
+///
+/// It observes every event posted to a topic, e.g. for logging or auditing, even if no
+/// `Listener` of the message type is registered.
+///
+/// Note: the struct which implements `AnyListener` need to be `Send` and `Sync`
+#[async_trait]
+pub trait AnyListener: Send + Sync + 'static {
+    /// handler callback to process event
+    async fn handle(&self, _: &AnyEvent<'_>) -> Result<(), ListenerError>;
 }
 
 impl Eventbus {
     /// create a `Topic` using a topic key
-    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    ///
+    /// With `TypeCheck::Deny`, if the topic is bound to another message type, the mismatch is
+    /// logged and posts through the returned `Topic` fail with `PostError::TypeMismatch`.
     pub async fn create_topic<T: 'static, K: Into<TopicKey>>(&self, topic_key: K) -> Topic<T> {
+        self.resolve_topic(topic_key.into())
+    }
+
+    /// create a `Topic` using a topic key
+    ///
+    /// # Errors
+    /// With `TypeCheck::Deny`, this method fails with `PostError::TypeMismatch` if the topic is
+    /// bound to another message type.
+    pub async fn try_create_topic<T: 'static, K: Into<TopicKey>>(
+        &self,
+        topic_key: K,
+    ) -> Result<Topic<T>, PostError> {
+        self.try_resolve_topic(topic_key.into())
+    }
+
+    /// register a listener to eventbus
+    ///
+    /// With `TypeCheck::Deny`, if the topic is bound to another message type, the mismatch is
+    /// logged and the listener is not registered.
+    pub async fn register<T: 'static, K: Into<TopicKey>, L: Listener<T>>(
+        &self,
+        topic_key: K,
+        listener: L,
+    ) -> EventListener<T> {
         let topic_key = topic_key.into();
-        let listeners = self
-            .inner
+        let event_listener = EventListener::<T>::new(topic_key.clone(), self.clone());
+        trace!("add event_listener: {:?}", event_listener);
+        self.inner
             .topic_handlers
-            .get_listener(topic_key.clone())
+            .add_listener(event_listener.rand_id, topic_key, None, Arc::new(listener))
             .await;
-        Topic {
-            key: topic_key,
-            bus: self.clone(),
-            event_listeners: listeners,
-        }
+        event_listener
     }
 
     /// register a listener to eventbus
-    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
-    pub async fn register<T: 'static, K: Into<TopicKey>, L: Listener<T>>(
+    ///
+    /// # Errors
+    /// With `TypeCheck::Deny`, this method fails with `PostError::TypeMismatch` if the topic is
+    /// bound to another message type, the listener is not registered then.
+    pub async fn try_register<T: 'static, K: Into<TopicKey>, L: Listener<T>>(
+        &self,
+        topic_key: K,
+        listener: L,
+    ) -> Result<EventListener<T>, PostError> {
+        let topic_key = topic_key.into();
+        let event_listener = EventListener::<T>::new(topic_key.clone(), self.clone());
+        trace!("add event_listener: {:?}", event_listener);
+        self.inner
+            .topic_handlers
+            .try_add_listener(event_listener.rand_id, topic_key, None, Arc::new(listener))
+            .await?;
+        Ok(event_listener)
+    }
+
+    /// register a listener of events of any message type
+    pub async fn register_any<K: Into<TopicKey>, L: AnyListener>(
         &self,
         topic_key: K,
         listener: L,
+    ) -> AnyEventListener {
+        let any_listener = AnyEventListener::new(topic_key, self.clone());
+        trace!("add any_listener: {:?}", any_listener);
+        self.inner.topic_handlers.registry.add_any(
+            any_listener.topic.clone(),
+            any_listener.rand_id,
+            Arc::new(listener),
+        );
+        any_listener
+    }
+
+    /// unregister a listener of events of any message type
+    pub async fn unregister_any(&self, any_listener: AnyEventListener) {
+        self.inner
+            .topic_handlers
+            .registry
+            .remove_any(&any_listener.topic, any_listener.rand_id);
+    }
+
+    /// register a listener to eventbus with `ListenerOptions`
+    pub async fn register_with<T: Send + Sync + 'static, K: Into<TopicKey>, L: Listener<T>>(
+        &self,
+        topic_key: K,
+        listener: L,
+        options: ListenerOptions,
+    ) -> EventListener<T> {
+        let topic_key = topic_key.into();
+        let event_listener = EventListener::<T>::new(topic_key.clone(), self.clone());
+        trace!(
+            "add event_listener: {:?} with {:?}",
+            event_listener,
+            options
+        );
+        let listener: Arc<dyn Listener<T>> = if options.is_limited() {
+            Arc::new(ManagedListener::new(
+                PlainListener(listener),
+                event_listener.rand_id,
+                event_listener.cancel.clone(),
+                self.inner.topic_handlers.runtime.clone(),
+                &options,
+            ))
+        } else {
+            Arc::new(listener)
+        };
+        self.inner
+            .topic_handlers
+            .add_listener(event_listener.rand_id, topic_key, options.group, listener)
+            .await;
+        event_listener
+    }
+
+    /// register a `ContextListener` to eventbus with `ListenerOptions`
+    pub async fn register_context<
+        T: Send + Sync + 'static,
+        K: Into<TopicKey>,
+        L: ContextListener<T>,
+    >(
+        &self,
+        topic_key: K,
+        listener: L,
+        options: ListenerOptions,
+    ) -> EventListener<T> {
+        let topic_key = topic_key.into();
+        let event_listener = EventListener::<T>::new(topic_key.clone(), self.clone());
+        trace!(
+            "add event_listener: {:?} with {:?}",
+            event_listener,
+            options
+        );
+        let listener = ManagedListener::new(
+            listener,
+            event_listener.rand_id,
+            event_listener.cancel.clone(),
+            self.inner.topic_handlers.runtime.clone(),
+            &options,
+        );
+        self.inner
+            .topic_handlers
+            .add_listener(
+                event_listener.rand_id,
+                topic_key,
+                options.group,
+                Arc::new(listener),
+            )
+            .await;
+        event_listener
+    }
+
+    /// register a listener to a consumer group of a topic
+    ///
+    /// Listeners in the same group share the load: each event goes to exactly one member,
+    /// chosen by the `GroupStrategy` of the group, and is re-dispatched to the other members
+    /// if the chosen one fails with a retryable error.
+    pub async fn register_group<T: 'static, K: Into<TopicKey>, G: Into<String>, L: Listener<T>>(
+        &self,
+        topic_key: K,
+        group: G,
+        listener: L,
     ) -> EventListener<T> {
         let topic_key = topic_key.into();
         let event_listener = EventListener::<T>::new(topic_key.clone(), self.clone());
         trace!("add event_listener: {:?}", event_listener);
         self.inner
             .topic_handlers
-            .add_listener(event_listener.rand_id, topic_key, listener)
+            .add_listener(
+                event_listener.rand_id,
+                topic_key,
+                Some(group.into()),
+                Arc::new(listener),
+            )
             .await;
         event_listener
     }
 
+    /// set the strategy of a consumer group, `RoundRobin` is used by default
+    pub async fn set_group_strategy<T: 'static, K: Into<TopicKey>, G: Into<String>>(
+        &self,
+        topic_key: K,
+        group: G,
+        strategy: GroupStrategy<T>,
+    ) {
+        let topic_handlers = &self.inner.topic_handlers;
+        if let Some(listeners) = topic_handlers.configure(topic_key.into()).await {
+            listeners
+                .lock()
+                .await
+                .set_group_strategy(group.into(), strategy);
+        }
+    }
+
+    /// deliver events of a topic in order per partition key
+    ///
+    /// Events whose messages share the same key are delivered strictly one after another,
+    /// in the order they are posted, while events of different keys are delivered concurrently.
+    pub async fn set_partition_key<
+        T: 'static,
+        K: Into<TopicKey>,
+        P: Hash,
+        F: Fn(&T) -> P + Send + Sync + 'static,
+    >(
+        &self,
+        topic_key: K,
+        extractor: F,
+    ) {
+        let topic_handlers = &self.inner.topic_handlers;
+        if let Some(listeners) = topic_handlers.configure(topic_key.into()).await {
+            listeners
+                .lock()
+                .await
+                .set_partitioner(Partitioner::new(key_hasher(extractor)));
+        }
+    }
+
+    /// deliver events of a topic through a bounded mailbox
+    ///
+    /// Once enabled, `post` only queues the event and returns, a dedicated task drains the
+    /// mailbox and delivers queued events one after another. `policy` decides what happens
+    /// when an event is posted to a full mailbox.
+    pub async fn enable_queue<T: Clone + Send + Sync + 'static, K: Into<TopicKey>>(
+        &self,
+        topic_key: K,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) {
+        let topic_key = topic_key.into();
+        let topic_handlers = &self.inner.topic_handlers;
+        let Some(listeners) = topic_handlers.configure::<T>(topic_key.clone()).await else {
+            return;
+        };
+        let mailbox = Mailbox::new::<T>(
+            topic_key,
+            capacity,
+            policy,
+            Event::clone,
+            Arc::downgrade(&self.inner.topic_handlers),
+        );
+        listeners.lock().await.set_mailbox(mailbox);
+    }
+
+    /// deliver events of a topic by spawning a task per listener on the runtime
+    ///
+    /// Listeners of an event run in parallel on the worker threads of the runtime, instead of
+    /// being polled together on the posting task. `post` returns once every task is done.
+    /// Members of consumer groups are still called on the posting task.
+    ///
+    /// The tasks outlive the borrow of the posted event, so the event is cloned once per post
+    /// and shared by the tasks. Messages which are expensive to clone can be wrapped in an `Arc`.
+    ///
+    /// `max_tasks` bounds the number of running tasks of the topic, further listeners wait
+    /// for a task to finish. A listener posting to the topic again from its task does not
+    /// wait for a permit, the nested event is delivered to the listeners on its task.
+    ///
+    /// With `ThreadRuntime`, e.g. the fallback of `TokioRuntime` for `post_blocking` outside
+    /// of a tokio runtime, each task is a new thread, so a post starts a thread per listener.
+    pub async fn enable_parallel<T: Clone + Send + Sync + 'static, K: Into<TopicKey>>(
+        &self,
+        topic_key: K,
+        max_tasks: Option<usize>,
+    ) {
+        let topic_handlers = &self.inner.topic_handlers;
+        let Some(listeners) = topic_handlers.configure::<T>(topic_key.into()).await else {
+            return;
+        };
+        let fanout = Fanout::new(
+            Event::clone,
+            max_tasks,
+            Arc::downgrade(&self.inner.topic_handlers),
+        );
+        listeners.lock().await.set_fanout(fanout);
+    }
+
+    /// number of events waiting in the mailbox of a queued topic
+    pub async fn queue_depth<T: 'static, K: Into<TopicKey>>(&self, topic_key: K) -> Option<usize> {
+        let listeners = self
+            .inner
+            .topic_handlers
+            .find_listener::<T>(&topic_key.into())
+            .await?;
+        let depth = listeners.lock().await.queue_depth();
+        depth
+    }
+
     /// unregister an event listener
-    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
     pub async fn unregister<T: 'static>(&self, event_listener: EventListener<T>) {
         self.inner
             .topic_handlers
             .remove_listener::<T, _>(event_listener.rand_id, event_listener.topic)
             .await;
+        event_listener.cancel.cancel();
     }
 
     /// post an event to eventbus
-    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
-    pub async fn post<T: Send + Sync + 'static>(&self, event: &Event<T>) {
-        trace!("recv post [{:?}]", event.topic);
-        self.inner.topic_handlers.notify(event).await;
+    ///
+    /// # Errors
+    /// Posting fails once the eventbus is shut down.
+    /// Posting to a queued topic fails if its mailbox is closed,
+    /// or full with `OverflowPolicy::Error`.
+    pub async fn post<T: Send + Sync + 'static>(&self, event: &Event<T>) -> Result<(), PostError> {
+        trace!("recv post [{:?}]", event.get_key());
+        let topic_handlers = &self.inner.topic_handlers;
+        topic_handlers.post(None, event, Delivery::Concurrent).await
+    }
+
+    /// list every topic of every message type, with the listeners subscribed to it
+    pub async fn topics(&self) -> Vec<TopicInfo> {
+        let entries = self.inner.topic_handlers.registry.entries();
+        let mut topics = future::join_all(
+            entries
+                .into_iter()
+                .map(|(key, entry)| async move { entry.describe(key).await }),
+        )
+        .await;
+        topics.sort_by(|a, b| (a.type_name, a.key.as_ref()).cmp(&(b.type_name, b.key.as_ref())));
+        topics
+    }
+
+    /// drop every topic which has neither listeners nor configuration, and no `Topic` handle
+    ///
+    /// Topics are pruned on unregistering their last listener, this sweeps the rest,
+    /// e.g. topics of dropped `Topic` handles. Returns the number of dropped topics.
+    pub async fn prune_topics(&self) -> usize {
+        let topic_handlers = &self.inner.topic_handlers;
+        topic_handlers.registry.prune(&topic_handlers.types)
+    }
+
+    /// shut down the eventbus
+    ///
+    /// New posts are rejected with `PostError::Shutdown` and the `ListenerContext` of every
+    /// listener is cancelled, so that cooperative handlers stop early. Then it waits at most
+    /// `timeout` for the events in flight, including those queued in mailboxes, to be delivered.
+    ///
+    /// # Errors
+    /// This method fails if some events are still in flight when `timeout` elapses.
+    pub async fn shutdown(&self, timeout: Duration) -> Result<(), ShutdownError> {
+        let lifecycle = &self.inner.topic_handlers.lifecycle;
+        trace!(
+            "shutdown eventbus, {} events in flight",
+            lifecycle.in_flight()
+        );
+        lifecycle.close();
+        self.inner.cancel.cancel();
+        let runtime = self.inner.topic_handlers.runtime.get();
+        let drained = runtime::timeout(runtime.as_ref(), timeout, lifecycle.idle()).await;
+        let entries = self.inner.topic_handlers.registry.entries();
+        future::join_all(
+            entries
+                .iter()
+                .map(|(key, entry)| entry.on_bus_shutdown(key)),
+        )
+        .await;
+        match drained {
+            Some(()) => Ok(()),
+            None => Err(ShutdownError::Timeout(lifecycle.in_flight())),
+        }
+    }
+
+    /// check if the eventbus is shut down
+    pub fn is_shutdown(&self) -> bool {
+        self.inner.topic_handlers.lifecycle.is_closed()
+    }
+
+    /// set the `Runtime` which spawns the tasks and runs the timers of the eventbus
+    ///
+    /// It can be replaced at any time, tasks which are already running keep running on the
+    /// previous runtime.
+    pub fn set_runtime<R: Runtime>(&self, runtime: R) {
+        self.inner.topic_handlers.runtime.set(Arc::new(runtime));
+    }
+
+    /// wait until the eventbus starts shutting down
+    pub async fn wait_for_shutdown(&self) {
+        self.inner.topic_handlers.lifecycle.closed().await
     }
 }
 
 impl<T: 'static> EventListener<T> {
     /// shorthand for unregister listener from eventbus
-    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
     pub async fn unregister(self) {
         self.bus.clone().unregister(self).await
     }
 }
 
+impl AnyEventListener {
+    /// shorthand for unregister listener from eventbus
+    pub async fn unregister(self) {
+        self.bus.clone().unregister_any(self).await
+    }
+}
+
 impl TopicHandlers {
-    async fn add_listener<T: 'static, K: Into<TopicKey>, L: Listener<T>>(
+    /// add a listener, it is not added if the topic is bound to another message type
+    async fn add_listener<T: 'static, K: Into<TopicKey>>(
         &self,
         rand_id: u64,
         topic_key: K,
-        listener: L,
+        group: Option<String>,
+        listener: Arc<dyn Listener<T>>,
     ) {
-        trace!("add listener: rand_id={}", rand_id);
-        let listeners = self.get_listener::<T, K>(topic_key).await;
-        listeners.lock().await.insert(rand_id, Box::new(listener));
+        if let Err(e) = self
+            .try_add_listener(rand_id, topic_key, group, listener)
+            .await
+        {
+            error!("listener {} not registered: {}", rand_id, e);
+        }
     }
 
-    async fn remove_listener<T: 'static, K: Into<TopicKey>>(&self, rand_id: u64, topic_key: K) {
-        let listeners = self.get_listener::<T, K>(topic_key).await;
-        listeners.lock().await.remove(&rand_id);
+    async fn try_add_listener<T: 'static, K: Into<TopicKey>>(
+        &self,
+        rand_id: u64,
+        topic_key: K,
+        group: Option<String>,
+        listener: Arc<dyn Listener<T>>,
+    ) -> Result<(), PostError> {
+        trace!("add listener: rand_id={}, group={:?}", rand_id, group);
+        let topic_key = topic_key.into();
+        let listeners = self.get_listener::<T, _>(topic_key.clone()).await?;
+        {
+            let mut guard = listeners.lock().await;
+            match group {
+                Some(group) => guard.insert_group_member(group, rand_id, listener.clone()),
+                None => guard.insert(rand_id, listener.clone()),
+            }
+        }
+        instrument::listener_added(&topic_key);
+        listener.on_register(&topic_key, rand_id).await;
+        Ok(())
     }
 
-    async fn get_listener<T: 'static, K: Into<TopicKey>>(&self, topic_key: K) -> EventListeners<T> {
-        let mut guard = self.inner.lock().await;
-        if !guard.contains::<TopicHandlersMap<T>>() {
-            guard.insert::<TopicHandlersMap<T>>(Default::default());
+    async fn remove_listener<T: 'static, K: Into<TopicKey>>(&self, rand_id: u64, topic_key: K) {
+        let topic_key = topic_key.into();
+        let Some(listeners) = self.find_listener::<T>(&topic_key).await else {
+            return;
+        };
+        let removed = listeners.lock().await.remove(rand_id);
+        drop(listeners);
+        self.prune_topic::<T>(&topic_key).await;
+        if let Some(listener) = removed {
+            instrument::listener_removed(&topic_key);
+            listener.on_unregister(&topic_key, rand_id).await;
         }
-        let inner = guard.get::<TopicHandlersMap<T>>().unwrap();
-        let mut inner_guard = inner.lock().await;
+    }
 
-        let topic_key = topic_key.into();
-        let listeners = inner_guard
-            .entry(topic_key)
-            .or_insert_with(Default::default);
+    /// get the listeners of a topic, create it if missing
+    ///
+    /// Fails if the topic is bound to another message type and the mode is `TypeCheck::Deny`.
+    async fn get_listener<T: 'static, K: Into<TopicKey>>(
+        &self,
+        topic_key: K,
+    ) -> Result<EventListeners<T>, PostError> {
+        let listeners = self
+            .registry
+            .get_or_insert::<T>(topic_key.into(), &self.types)?;
         trace!("current listeners: {}", listeners.lock().await.len());
-        listeners.clone()
+        Ok(listeners)
+    }
+
+    /// get the listeners of a topic to configure, `None` if it is bound to another type
+    async fn configure<T: 'static>(&self, topic_key: TopicKey) -> Option<EventListeners<T>> {
+        self.get_listener(topic_key)
+            .await
+            .inspect_err(|e| error!("topic not configured: {}", e))
+            .ok()
+    }
+
+    /// get the listeners of a topic without creating it
+    async fn find_listener<T: 'static>(&self, topic_key: &TopicKey) -> Option<EventListeners<T>> {
+        self.registry.get::<T>(topic_key)
+    }
+
+    /// drop the topic if it is idle
+    async fn prune_topic<T: 'static>(&self, topic_key: &TopicKey) {
+        if self
+            .registry
+            .remove_if_prunable::<T>(topic_key, &self.types)
+        {
+            trace!("prune idle topic [{}]", topic_key);
+        }
     }
 
-    async fn notify<T: Send + Sync + 'static>(&self, event: &Event<T>) {
-        let listeners = self.get_listener::<T, _>(event.topic.clone()).await;
-        let guard = listeners.lock().await;
-        future::join_all(guard.iter().map(|(_, listener)| {
-            trace!("notify listener for event [{:?}]", event.topic);
+    /// post an event, to the listeners of a `Topic` handle if they are resolved already
+    pub(crate) async fn post<T: Send + Sync + 'static>(
+        &self,
+        resolved: Option<&EventListeners<T>>,
+        event: &Event<T>,
+        delivery: Delivery,
+    ) -> Result<(), PostError> {
+        let mut subscribed = 0;
+        let span = spans::post::<T>(event.get_key());
+        let dispatch = self.dispatch(resolved, event, delivery, &mut subscribed);
+        let result = spans::instrument(dispatch, span).await;
+        if self.wiretap.is_enabled() {
+            let outcome = match &result {
+                Ok(Some(failures)) => PostOutcome::Delivered {
+                    failures: *failures,
+                },
+                Ok(None) => PostOutcome::Queued,
+                Err(e) => PostOutcome::Rejected(e),
+            };
+            self.wiretap.record(event, subscribed, outcome);
+        }
+        result.map(|_| ())
+    }
+
+    /// deliver or queue an event, returns the number of failures if it is delivered
+    async fn dispatch<T: Send + Sync + 'static>(
+        &self,
+        resolved: Option<&EventListeners<T>>,
+        event: &Event<T>,
+        delivery: Delivery,
+        subscribed: &mut usize,
+    ) -> Result<Option<usize>, PostError> {
+        let guard = self.lifecycle.enter().ok_or(PostError::Shutdown)?;
+        let found;
+        self.types.check::<T>(event.get_key())?;
+        let listeners = match resolved {
+            Some(listeners) => Some(listeners),
+            None => {
+                found = self.find_listener::<T>(event.get_key()).await;
+                found.as_ref()
+            }
+        };
+        instrument::posted::<T>(event.get_key());
+        let any_listeners = match listeners {
+            Some(listeners) => listeners.node().any_listeners(),
+            None => self
+                .registry
+                .node(event.get_key())
+                .and_then(|node| node.any_listeners()),
+        };
+        if let Some(any_listeners) = any_listeners {
+            self.notify_any(&any_listeners, event).await;
+        }
+        let Some(listeners) = listeners else {
+            trace!("no listener of topic [{}]", event.get_key());
+            return Ok(Some(0));
+        };
+        if let Some(snapshot) = listeners.snapshot() {
+            *subscribed = snapshot.len();
+            return Ok(Some(self.deliver_plain(&snapshot, event, delivery).await));
+        }
+        let mailbox = {
+            let guard = listeners.lock().await;
+            *subscribed = guard.len();
+            guard.mailbox()
+        };
+        match mailbox {
+            Some(mailbox) => mailbox.push(event, guard).await.map(|()| None),
+            None => Ok(Some(self.deliver(listeners, event, delivery).await)),
+        }
+    }
+
+    async fn notify_any<T: Send + Sync + 'static>(
+        &self,
+        listeners: &AnyTopicListeners,
+        event: &Event<T>,
+    ) {
+        let event = AnyEvent::new(event);
+        future::join_all(listeners.iter().map(|(rand_id, listener)| async {
+            if let Err(e) = listener.handle(&event).await {
+                self.errors.report(&ListenerFailure {
+                    topic: event.topic(),
+                    type_name: event.type_name(),
+                    listener_id: *rand_id,
+                    group: None,
+                    error: &e,
+                })
+            }
+        }))
+        .await;
+    }
+
+    pub(crate) async fn notify<T: Send + Sync + 'static>(&self, event: &Event<T>) {
+        if let Some(listeners) = self.find_listener::<T>(event.get_key()).await {
+            self.deliver(&listeners, event, Delivery::Concurrent).await;
+        }
+    }
+
+    /// deliver an event to a snapshot of plain listeners, returns the number of failures
+    async fn deliver_plain<T: Send + Sync + 'static>(
+        &self,
+        listeners: &[(u64, Arc<dyn Listener<T>>)],
+        event: &Event<T>,
+        delivery: Delivery,
+    ) -> usize {
+        // a single listener, the most common case, is awaited without allocating a join
+        if let [(rand_id, listener)] = listeners {
+            return usize::from(!self.notify_listener(*rand_id, listener, event).await);
+        }
+        match delivery {
+            Delivery::Concurrent => {}
+            #[cfg(feature = "sync_parallel")]
+            Delivery::Parallel => return self.deliver_parallel(listeners, &[], event),
+        }
+        future::join_all(
+            listeners
+                .iter()
+                .map(|(rand_id, listener)| self.notify_listener(*rand_id, listener, event)),
+        )
+        .await
+        .into_iter()
+        .filter(|ok| !ok)
+        .count()
+    }
+
+    /// deliver an event to a listener, returns `false` if it failed
+    pub(crate) async fn notify_listener<T: Send + Sync + 'static>(
+        &self,
+        rand_id: u64,
+        listener: &Arc<dyn Listener<T>>,
+        event: &Event<T>,
+    ) -> bool {
+        trace!("notify listener for event [{:?}]", event.get_key());
+        let span = spans::listener::<T>(event.get_key());
+        spans::instrument(
             async {
-                let result = listener.handle(event).await;
-                if let Err(e) = result {
+                let timer = instrument::start();
+                let result = handle_caught(listener.as_ref(), event).await;
+                timer.observe(event.get_key(), result.is_ok());
+                if let Err(e) = &result {
+                    self.errors.report(&ListenerFailure {
+                        topic: event.get_key(),
+                        type_name: type_name::<T>(),
+                        listener_id: rand_id,
+                        group: None,
+                        error: e,
+                    })
+                }
+                result.is_ok()
+            },
+            span,
+        )
+        .await
+    }
+
+    /// deliver an event to the listeners, returns the number of failed listeners and groups
+    async fn deliver<T: Send + Sync + 'static>(
+        &self,
+        listeners: &EventListeners<T>,
+        event: &Event<T>,
+        delivery: Delivery,
+    ) -> usize {
+        if let Some(snapshot) = listeners.snapshot() {
+            return self.deliver_plain(&snapshot, event, delivery).await;
+        }
+        // the ticket is taken under the topic lock, so it keeps the order of the posts
+        let (plan, ticket) = {
+            let mut guard = listeners.lock().await;
+            (guard.dispatch_plan(event), guard.partition_ticket(event))
+        };
+        if let Some(ticket) = &ticket {
+            ticket.wait().await;
+        }
+        #[cfg(feature = "sync_parallel")]
+        if delivery == Delivery::Parallel && plan.fanout.is_none() {
+            return self.deliver_parallel(&plan.listeners, &plan.groups, event);
+        }
+        let listeners = async {
+            match &plan.fanout {
+                Some(fanout) => fanout.deliver(self, &plan.listeners, event).await,
+                None => {
+                    self.deliver_plain(&plan.listeners, event, Delivery::Concurrent)
+                        .await
+                }
+            }
+        };
+        let groups = future::join_all(
+            plan.groups
+                .iter()
+                .map(|(group, members)| notify_group(&self.errors, group, members, event)),
+        );
+        let (failures, groups) = future::join(listeners, groups).await;
+        failures + groups.into_iter().filter(|ok| !ok).count()
+    }
+
+    /// deliver an event to listeners and groups in parallel on the rayon thread pool,
+    /// returns the number of failed listeners and groups
+    #[cfg(feature = "sync_parallel")]
+    fn deliver_parallel<T: Send + Sync + 'static>(
+        &self,
+        listeners: &[(u64, Arc<dyn Listener<T>>)],
+        groups: &[(String, Vec<GroupMember<T>>)],
+        event: &Event<T>,
+    ) -> usize {
+        let (listeners, groups) = rayon::join(
+            || {
+                listeners
+                    .par_iter()
+                    .filter(|(rand_id, listener)| {
+                        !block_on(self.notify_listener(*rand_id, listener, event))
+                    })
+                    .count()
+            },
+            || {
+                groups
+                    .par_iter()
+                    .filter(|(group, members)| {
+                        !block_on(notify_group(&self.errors, group, members, event))
+                    })
+                    .count()
+            },
+        );
+        listeners + groups
+    }
+}
+
+/// How the listeners of an event are called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Delivery {
+    /// polled concurrently on the posting task
+    Concurrent,
+    /// called in parallel on the rayon thread pool, for blocking posts
+    #[cfg(feature = "sync_parallel")]
+    Parallel,
+}
+
+/// handle an event, a panic of the listener fails with `ListenerError::Panicked`
+async fn handle_caught<T: Send + Sync + 'static>(
+    listener: &dyn Listener<T>,
+    event: &Event<T>,
+) -> Result<(), ListenerError> {
+    AssertUnwindSafe(listener.handle(event))
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|payload| Err(listener_panicked(payload)))
+}
+
+/// deliver an event to the first member of a consumer group which processes it successfully,
+/// returns `false` if no member did
+///
+/// The event is only re-dispatched to the next member if the error is retryable.
+async fn notify_group<T: Send + Sync + 'static>(
+    errors: &ErrorHandlers,
+    group: &str,
+    members: &[GroupMember<T>],
+    event: &Event<T>,
+) -> bool {
+    for member in members {
+        trace!(
+            "notify member {} of group [{}] for event [{:?}]",
+            member.rand_id,
+            group,
+            event.get_key()
+        );
+        let _in_flight = member.begin();
+        let timer = instrument::start();
+        let span = spans::listener::<T>(event.get_key());
+        let result = spans::instrument(handle_caught(member.listener.as_ref(), event), span).await;
+        timer.observe(event.get_key(), result.is_ok());
+        match result {
+            Ok(()) => return true,
+            Err(e) => {
+                errors.report(&ListenerFailure {
+                    topic: event.get_key(),
+                    type_name: type_name::<T>(),
+                    listener_id: member.rand_id,
+                    group: Some(group),
+                    error: &e,
+                });
+                if !e.is_retryable() {
                     error!(
-                        "listener of topic [{}] failed to process event: {:?}",
-                        event.topic, e
-                    )
+                        "member {} of group [{}] of topic [{}] failed without retry: {}",
+                        member.rand_id,
+                        group,
+                        event.get_key(),
+                        e
+                    );
+                    return false;
                 }
             }
-        }))
-        .await;
+        }
     }
+    error!(
+        "no member of group [{}] of topic [{}] processed event",
+        group,
+        event.get_key()
+    );
+    false
 }
 
 impl<T: Send + Sync + 'static> Topic<T> {
-    /// shorthand for post event to eventbus
-    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
-    pub async fn post(&self, event: &Event<T>) {
-        self.bus.post(event).await;
+    /// post an event to the listeners of the topic
+    ///
+    /// Events of the topic are delivered to the listeners resolved at creation of the `Topic`,
+    /// without looking them up. Events of other topics are posted to the eventbus.
+    pub async fn post(&self, event: &Event<T>) -> Result<(), PostError> {
+        if *event.get_key() != self.key {
+            return self.bus.post(event).await;
+        }
+        trace!("recv post [{:?}]", event.get_key());
+        let topic_handlers = &self.bus.inner.topic_handlers;
+        topic_handlers
+            .post(Some(&self.event_listeners), event, Delivery::Concurrent)
+            .await
     }
 
     /// shorthand for post message to eventbus
-    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
-    pub async fn post_message(&self, message: T) {
+    pub async fn post_message(&self, message: T) -> Result<(), PostError> {
         let event = self.create_event(message);
-        self.post(&event).await;
+        self.post(&event).await
     }
 }
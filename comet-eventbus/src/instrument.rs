@@ -0,0 +1,124 @@
+#![cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+
+use crate::TopicKey;
+#[cfg(feature = "metrics")]
+use arc_swap::ArcSwapOption;
+#[cfg(feature = "metrics")]
+use std::collections::HashSet;
+#[cfg(feature = "metrics")]
+use std::sync::Arc;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// events posted, labelled by `topic` and message `type`
+pub const POSTS: &str = "comet_eventbus_posts_total";
+/// events handled successfully by a listener, labelled by `topic`
+pub const DELIVERIES: &str = "comet_eventbus_deliveries_total";
+/// events a listener failed to handle, labelled by `topic`
+pub const FAILURES: &str = "comet_eventbus_failures_total";
+/// seconds a listener takes to handle an event, labelled by `topic`
+pub const LISTENER_DURATION: &str = "comet_eventbus_listener_duration_seconds";
+/// events waiting in the mailbox of a queued topic, labelled by `topic`
+pub const QUEUE_DEPTH: &str = "comet_eventbus_queue_depth";
+/// listeners subscribed, labelled by `topic`
+pub const LISTENERS: &str = "comet_eventbus_listeners";
+/// events received from remote eventbuses, labelled by `topic`
+pub const BRIDGE_RECEIVED: &str = "comet_eventbus_bridge_received_total";
+/// events sent to remote eventbuses, labelled by `topic`
+pub const BRIDGE_SENT: &str = "comet_eventbus_bridge_sent_total";
+
+/// `topic` label of the topics which are not labelled by their key
+pub const OTHER_TOPICS: &str = "other";
+
+/// topics labelled by their key
+#[cfg(feature = "metrics")]
+static LABELLED: ArcSwapOption<HashSet<TopicKey>> = ArcSwapOption::const_empty();
+
+/// label the metrics of the given topics by their key, replacing the topics given before
+///
+/// The metrics of other topics share the [`OTHER_TOPICS`] label, so the number of series
+/// stays bounded whatever topics are used. No topic is labelled by default.
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub fn label_topics<K: Into<TopicKey>>(topics: impl IntoIterator<Item = K>) {
+    LABELLED.store(Some(Arc::new(topics.into_iter().map(Into::into).collect())));
+}
+
+#[cfg(feature = "metrics")]
+fn topic_label(topic: &TopicKey) -> metrics::SharedString {
+    match &*LABELLED.load() {
+        Some(labelled) if labelled.contains(topic) => topic.to_string().into(),
+        _ => metrics::SharedString::const_str(OTHER_TOPICS),
+    }
+}
+
+/// Measure the time a listener takes to handle an event
+pub(crate) struct Timer {
+    #[cfg(feature = "metrics")]
+    start: Instant,
+}
+
+pub(crate) fn posted<T>(topic: &TopicKey) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(
+        POSTS,
+        "topic" => topic_label(topic),
+        "type" => std::any::type_name::<T>()
+    )
+    .increment(1);
+}
+
+/// start timing a listener
+pub(crate) fn start() -> Timer {
+    Timer {
+        #[cfg(feature = "metrics")]
+        start: Instant::now(),
+    }
+}
+
+/// count the outcome of a listener handling an event, members of consumer groups included
+pub(crate) fn delivered(topic: &TopicKey, ok: bool) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(
+        if ok { DELIVERIES } else { FAILURES },
+        "topic" => topic_label(topic)
+    )
+    .increment(1);
+}
+
+pub(crate) fn queue_depth(topic: &TopicKey, depth: usize) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(QUEUE_DEPTH, "topic" => topic_label(topic)).set(depth as f64);
+}
+
+pub(crate) fn listener_added(topic: &TopicKey) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(LISTENERS, "topic" => topic_label(topic)).increment(1.0);
+}
+
+pub(crate) fn listener_removed(topic: &TopicKey) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(LISTENERS, "topic" => topic_label(topic)).decrement(1.0);
+}
+
+#[cfg(feature = "bridge")]
+pub(crate) fn bridge_received(topic: &TopicKey) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(BRIDGE_RECEIVED, "topic" => topic_label(topic)).increment(1);
+}
+
+#[cfg(feature = "bridge")]
+pub(crate) fn bridge_sent(topic: &TopicKey, clients: usize) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(BRIDGE_SENT, "topic" => topic_label(topic)).increment(clients as u64);
+}
+
+impl Timer {
+    /// record the duration and the outcome of a listener
+    pub(crate) fn observe(self, topic: &TopicKey, ok: bool) {
+        #[cfg(feature = "metrics")]
+        metrics::histogram!(LISTENER_DURATION, "topic" => topic_label(topic))
+            .record(self.start.elapsed());
+        delivered(topic, ok);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{Listener, TopicKey};
+
+/// Snapshot of a topic, returned by `Eventbus::topics`
+#[derive(Debug, Clone)]
+pub struct TopicInfo {
+    /// key of the topic
+    pub key: TopicKey,
+    /// rust type name of messages posted to the topic
+    pub type_name: &'static str,
+    /// listeners subscribed to the topic, including members of consumer groups
+    pub listeners: Vec<ListenerInfo>,
+}
+
+/// Snapshot of a listener subscribed to a topic
+#[derive(Debug, Clone)]
+pub struct ListenerInfo {
+    /// id of the listener, see `EventListener::id`
+    pub id: u64,
+    /// consumer group the listener belongs to
+    pub group: Option<String>,
+    /// name of the listener, see `Listener::name`
+    pub name: Option<String>,
+}
+
+impl TopicInfo {
+    /// number of subscribed listeners
+    pub fn listener_count(&self) -> usize {
+        self.listeners.len()
+    }
+}
+
+impl ListenerInfo {
+    pub(crate) fn new<T: 'static>(
+        id: u64,
+        group: Option<String>,
+        listener: &dyn Listener<T>,
+    ) -> Self {
+        Self {
+            id,
+            group,
+            name: listener.name().map(str::to_owned),
+        }
+    }
+}
@@ -5,6 +5,8 @@
 //! # Get Started
 //!
 //! comet-eventbus is async-first-classed. We recommend you to use async API.
+//! Blocking code can register a `SyncListener` and post with `Eventbus::post_blocking`
+//! on the same eventbus.
 //!
 //! Add following code to your `Cargo.toml`:
 //! ```toml
@@ -30,7 +32,7 @@
 //!     let topic = eventbus.create_topic("my awsome topic").await;
 //!
 //!     // post message to a topic
-//!     topic.post_message(Message { content: 0 }).await;
+//!     topic.post_message(Message { content: 0 }).await.unwrap();
 //! }
 //! ```
 //!
@@ -47,91 +49,258 @@
 #[macro_use]
 extern crate log;
 
-use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 
-#[cfg(not(any(feature = "async", feature = "sync")))]
-compile_error!("Either `async` or `sync` feature must be enabled");
-
-#[cfg(all(feature = "async", feature = "sync"))]
-compile_error!("The `async` and `sync` features cannot be enabled simultaneously");
-
-#[cfg(feature = "async")]
 pub use async_trait::async_trait;
 
+mod any_listener;
+mod blocking;
 /// bridge `Eventbus` from an external source
 #[cfg(feature = "bridge")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bridge")))]
 pub mod bridge;
-mod event;
+mod consumer_group;
+mod context;
+mod error_handler;
 mod event_listener;
-#[cfg(feature = "async")]
+mod fanout;
 mod impl_async;
-#[cfg(feature = "sync")]
-mod impl_sync;
+/// names of the metrics emitted through the `metrics` facade
+///
+/// Metrics are only emitted with the `metrics` feature. Only the topics passed to
+/// `label_topics` are labelled by their key, the others share the `other` label, so random
+/// topics such as the reply topics of services do not each get their own series.
+pub mod instrument;
+mod introspect;
+mod listener_options;
+mod local;
+mod mailbox;
+mod native;
+mod partition;
+mod registry;
+mod runtime;
+mod shutdown;
+mod spans;
+mod static_bus;
 #[cfg(test)]
 mod tests;
 mod topic;
-mod topic_key;
+mod topic_listeners;
+mod type_check;
+mod wiretap;
 
-pub use event::Event;
+pub use any_listener::{AnyEvent, AnyEventListener};
+pub use comet_eventbus_core::{Event, ListenerError, ListenerResultExt, TopicKey};
+pub use consumer_group::GroupStrategy;
+pub use error_handler::{ErrorHandler, ListenerFailure};
 pub use event_listener::EventListener;
+pub use introspect::{ListenerInfo, TopicInfo};
+pub use static_bus::StaticTopic;
 pub use topic::Topic;
-pub use topic_key::TopicKey;
-
-#[cfg(feature = "async")]
-#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
-pub use impl_async::Listener;
-#[cfg(feature = "sync")]
-#[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
-pub use impl_sync::Listener;
-
-#[cfg(feature = "sync")]
-use parking_lot::Mutex;
-#[cfg(feature = "async")]
+pub use topic_listeners::{TopicEntry, TopicGuard, TopicListeners};
+pub use type_check::{TypeCheck, TypeConflict};
+pub use wiretap::{PostOutcome, PostRecord, Wiretap};
+
+pub use blocking::SyncListener;
+pub use context::ListenerContext;
+pub use impl_async::{AnyListener, ContextListener, Listener, Listener as AsyncListener};
+pub use listener_options::ListenerOptions;
+pub use local::{LocalEventListener, LocalEventbus, LocalListener, LocalTopic};
+pub use mailbox::OverflowPolicy;
+pub use native::NativeListener;
+#[cfg(feature = "tokio")]
+pub use runtime::TokioRuntime;
+pub use runtime::{Runtime, ThreadRuntime};
+
 use tokio::sync::Mutex;
 
-/// An asynchronous `Eventbus` to interact with
+/// An `Eventbus` to interact with, from async or blocking code
 #[derive(Debug, Clone)]
 pub struct Eventbus {
     inner: Arc<EventbusInner>,
 }
 
 /// short hand of event listeners set
-pub type EventListeners<T> = Arc<Mutex<HashMap<u64, Box<dyn Listener<T>>>>>;
-/// short hand of topic to handlers map
-pub type TopicHandlersMap<T> = Arc<Mutex<HashMap<TopicKey, EventListeners<T>>>>;
+pub type EventListeners<T> = Arc<TopicEntry<T>>;
 
 #[derive(Debug)]
 struct EventbusInner {
     topic_handlers: Arc<TopicHandlers>,
+    /// parent of the cancellation tokens of all listeners
+    cancel: tokio_util::sync::CancellationToken,
 }
 
 #[derive(Debug)]
 struct TopicHandlers {
-    inner: Mutex<anymap::Map<dyn anymap::any::Any + Send + Sync>>,
+    registry: registry::Registry,
+    lifecycle: Arc<shutdown::Lifecycle>,
+    types: type_check::TypeRegistry,
+    wiretap: wiretap::Tap,
+    errors: error_handler::ErrorHandlers,
+    runtime: runtime::RuntimeSlot,
 }
 
-/// Error of Listener exceptions
+/// Error of posting an event
 #[derive(Debug, thiserror::Error)]
-pub enum ListenerError {
-    /// error of bridge feature
-    #[cfg(feature = "bridge")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "bridge")))]
-    #[error(transparent)]
-    BridgeError(#[from] bridge::BridgeError),
+pub enum PostError {
+    /// the mailbox of a queued topic is full
+    #[error("mailbox of topic [{0}] is full")]
+    QueueFull(TopicKey),
+    /// the mailbox of a queued topic is closed
+    #[error("mailbox of topic [{0}] is closed")]
+    QueueClosed(TopicKey),
+    /// the eventbus is shut down
+    #[error("eventbus is shut down")]
+    Shutdown,
+    /// the topic is bound to another message type, see `TypeCheck`
+    #[error("topic [{topic}] is bound to messages of type {expected}, not {found}")]
+    TypeMismatch {
+        /// key of the topic
+        topic: TopicKey,
+        /// type name of messages the topic is bound to
+        expected: &'static str,
+        /// type name of the rejected message
+        found: &'static str,
+    },
+}
+
+/// Error of shutting down an eventbus
+#[derive(Debug, thiserror::Error)]
+pub enum ShutdownError {
+    /// events were still in flight when the shutdown deadline passed
+    #[error("{0} events still in flight after the shutdown deadline")]
+    Timeout(usize),
 }
 
 impl Eventbus {
     /// create an new eventbus
     pub fn new() -> Self {
+        Self::with_type_check(TypeCheck::Off)
+    }
+
+    /// create an new eventbus which checks that every topic is used with a single message type
+    pub fn with_type_check(type_check: TypeCheck) -> Self {
         Self {
             inner: Arc::new(EventbusInner {
-                topic_handlers: Arc::new(TopicHandlers::new()),
+                topic_handlers: Arc::new(TopicHandlers::new(type_check)),
+                cancel: Default::default(),
             }),
         }
     }
+
+    /// topic keys used with more than one message type, tracked unless `TypeCheck::Off`
+    pub fn type_conflicts(&self) -> Vec<TypeConflict> {
+        self.inner.topic_handlers.types.conflicts()
+    }
+
+    /// set the `Wiretap` which sees every post, replacing the previous one
+    pub fn set_wiretap<W: Wiretap>(&self, wiretap: W) {
+        self.inner
+            .topic_handlers
+            .wiretap
+            .set(Some(Arc::new(wiretap)));
+    }
+
+    /// remove the `Wiretap`
+    pub fn clear_wiretap(&self) {
+        self.inner.topic_handlers.wiretap.set(None);
+    }
+
+    /// render messages of type `T` with `Debug` in `PostRecord`s
+    pub fn wiretap_debug<T: Debug + 'static>(&self) {
+        self.inner.topic_handlers.wiretap.render::<T>();
+    }
+
+    /// set the `ErrorHandler` of listener failures on every topic, replacing the previous one
+    ///
+    /// Failures are logged with `error!` when no handler applies.
+    pub fn set_error_handler<H: ErrorHandler>(&self, handler: H) {
+        self.inner
+            .topic_handlers
+            .errors
+            .set(Some(Arc::new(handler)));
+    }
+
+    /// remove the global `ErrorHandler`
+    pub fn clear_error_handler(&self) {
+        self.inner.topic_handlers.errors.set(None);
+    }
+
+    /// set the `ErrorHandler` of listener failures on a topic, which takes precedence over the
+    /// global one
+    pub fn set_topic_error_handler<K: Into<TopicKey>, H: ErrorHandler>(
+        &self,
+        topic: K,
+        handler: H,
+    ) {
+        self.inner
+            .topic_handlers
+            .errors
+            .set_topic(topic.into(), Some(Arc::new(handler)));
+    }
+
+    /// remove the `ErrorHandler` of a topic
+    pub fn clear_topic_error_handler<K: Into<TopicKey>>(&self, topic: K) {
+        self.inner
+            .topic_handlers
+            .errors
+            .set_topic(topic.into(), None);
+    }
+
+    /// create a `Topic` without waiting for its listeners
+    ///
+    /// If the topic is bound to another message type, the mismatch is logged and the `Topic`
+    /// holds listeners which are not registered, posts through it fail with the mismatch.
+    pub(crate) fn resolve_topic<T: 'static>(&self, topic_key: TopicKey) -> Topic<T> {
+        self.try_resolve_topic(topic_key.clone())
+            .unwrap_or_else(|e| {
+                error!("topic not created: {}", e);
+                Topic {
+                    key: topic_key.clone(),
+                    bus: self.clone(),
+                    event_listeners: registry::Registry::detached(topic_key),
+                }
+            })
+    }
+
+    /// create a `Topic` without waiting for its listeners, fails on type mismatch
+    pub(crate) fn try_resolve_topic<T: 'static>(
+        &self,
+        topic_key: TopicKey,
+    ) -> Result<Topic<T>, PostError> {
+        let topic_handlers = &self.inner.topic_handlers;
+        let listeners = topic_handlers
+            .registry
+            .get_or_insert::<T>(topic_key, &topic_handlers.types)?;
+        Ok(Topic {
+            key: listeners.node().key().clone(),
+            bus: self.clone(),
+            event_listeners: listeners,
+        })
+    }
+}
+
+impl From<PostError> for ListenerError {
+    fn from(error: PostError) -> Self {
+        // a full mailbox may have room once its listeners catch up
+        match error {
+            PostError::QueueFull(_) => Self::retryable(error),
+            _ => Self::fatal(error),
+        }
+    }
+}
+
+/// convert the payload of a caught panic
+pub(crate) fn listener_panicked(payload: Box<dyn std::any::Any + Send>) -> ListenerError {
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    };
+    ListenerError::Panicked(message)
 }
 
 impl Default for Eventbus {
@@ -141,9 +310,14 @@ impl Default for Eventbus {
 }
 
 impl TopicHandlers {
-    fn new() -> Self {
+    fn new(type_check: TypeCheck) -> Self {
         Self {
-            inner: Mutex::new(anymap::Map::new()),
+            registry: registry::Registry::new(),
+            lifecycle: Default::default(),
+            types: type_check::TypeRegistry::new(type_check),
+            wiretap: Default::default(),
+            errors: Default::default(),
+            runtime: Default::default(),
         }
     }
 }
@@ -0,0 +1,156 @@
+use crate::runtime::{self, RuntimeSlot};
+use crate::{ContextListener, Event, Listener, ListenerContext, ListenerError, TopicKey};
+use async_trait::async_trait;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+/// Options of a listener registration
+///
+/// ## Example:
+/// ```
+/// use comet_eventbus::ListenerOptions;
+/// use std::time::Duration;
+///
+/// ListenerOptions::new()
+///     .group("workers")
+///     .max_concurrency(4)
+///     .timeout(Duration::from_secs(1));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ListenerOptions {
+    pub(crate) group: Option<String>,
+    pub(crate) max_concurrency: Option<usize>,
+    pub(crate) timeout: Option<Duration>,
+}
+
+/// Listener wrapper which enforces `ListenerOptions` and provides `ListenerContext`
+pub(crate) struct ManagedListener<L> {
+    inner: L,
+    listener_id: u64,
+    token: CancellationToken,
+    semaphore: Option<Semaphore>,
+    timeout: Option<Duration>,
+    /// runtime of the eventbus, read at each invocation so that it follows `set_runtime`
+    runtime: RuntimeSlot,
+}
+
+/// Adapter of a `Listener` which ignores the `ListenerContext`
+pub(crate) struct PlainListener<L>(pub(crate) L);
+
+impl ListenerOptions {
+    /// create default options: no group, unbounded concurrency and no timeout
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// register the listener to a consumer group
+    pub fn group<G: Into<String>>(mut self, group: G) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    /// limit the number of in-flight invocations of the listener
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency.max(1));
+        self
+    }
+
+    /// cancel an invocation which takes longer than `timeout`,
+    /// it fails with `ListenerError::Timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub(crate) fn is_limited(&self) -> bool {
+        self.max_concurrency.is_some() || self.timeout.is_some()
+    }
+}
+
+impl<L> ManagedListener<L> {
+    pub(crate) fn new(
+        inner: L,
+        listener_id: u64,
+        token: CancellationToken,
+        runtime: RuntimeSlot,
+        options: &ListenerOptions,
+    ) -> Self {
+        Self {
+            inner,
+            listener_id,
+            token,
+            semaphore: options.max_concurrency.map(Semaphore::new),
+            timeout: options.timeout,
+            runtime,
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Send + Sync + 'static, L: ContextListener<T>> Listener<T> for ManagedListener<L> {
+    async fn handle(&self, event: &Event<T>) -> Result<(), ListenerError> {
+        let _permit = match &self.semaphore {
+            Some(semaphore) => Some(semaphore.acquire().await.expect("semaphore never closed")),
+            None => None,
+        };
+        let cx = ListenerContext::new(
+            self.listener_id,
+            self.token.child_token(),
+            self.timeout.map(|timeout| Instant::now() + timeout),
+        );
+        match self.timeout {
+            Some(timeout) => {
+                let handle = self.inner.handle(event, &cx);
+                match runtime::timeout(self.runtime.get().as_ref(), timeout, handle).await {
+                    Some(result) => result,
+                    None => {
+                        // the timed out invocation is dropped, tasks it spawned observe the token
+                        cx.token().cancel();
+                        Err(ListenerError::Timeout(timeout))
+                    }
+                }
+            }
+            None => self.inner.handle(event, &cx).await,
+        }
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+
+    async fn on_register(&self, topic: &TopicKey, listener_id: u64) {
+        self.inner.on_register(topic, listener_id).await
+    }
+
+    async fn on_unregister(&self, topic: &TopicKey, listener_id: u64) {
+        self.inner.on_unregister(topic, listener_id).await
+    }
+
+    async fn on_bus_shutdown(&self, topic: &TopicKey, listener_id: u64) {
+        self.inner.on_bus_shutdown(topic, listener_id).await
+    }
+}
+
+#[async_trait]
+impl<T: Send + Sync + 'static, L: Listener<T>> ContextListener<T> for PlainListener<L> {
+    async fn handle(&self, event: &Event<T>, _: &ListenerContext) -> Result<(), ListenerError> {
+        self.0.handle(event).await
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.0.name()
+    }
+
+    async fn on_register(&self, topic: &TopicKey, listener_id: u64) {
+        self.0.on_register(topic, listener_id).await
+    }
+
+    async fn on_unregister(&self, topic: &TopicKey, listener_id: u64) {
+        self.0.on_unregister(topic, listener_id).await
+    }
+
+    async fn on_bus_shutdown(&self, topic: &TopicKey, listener_id: u64) {
+        self.0.on_bus_shutdown(topic, listener_id).await
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{Event, ListenerError, TopicKey};
+use async_trait::async_trait;
+use comet_eventbus_core::{IdSource, RandomIds};
+use futures::future;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// Event listener of a `LocalEventbus`
+///
+/// Unlike `Listener`, neither the listener nor the futures it returns need to be `Send`,
+/// so it can hold `Rc` based state.
+#[async_trait(?Send)]
+pub trait LocalListener<T>: 'static {
+    /// handler callback to process event
+    async fn handle(&self, _: &Event<T>) -> Result<(), ListenerError>;
+}
+
+type LocalListeners<T> = HashMap<TopicKey, HashMap<u64, Rc<dyn LocalListener<T>>>>;
+
+/// A single-threaded eventbus for `!Send` messages and listeners
+///
+/// It is meant to be used on a single thread, e.g. in a tokio `LocalSet` or an UI event loop.
+///
+/// ## Example:
+/// ```
+/// use comet_eventbus::{async_trait, Event, ListenerError, LocalEventbus, LocalListener};
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// struct Clicks(Rc<Cell<u32>>);
+///
+/// #[async_trait(?Send)]
+/// impl LocalListener<Rc<str>> for Clicks {
+///     async fn handle(&self, _: &Event<Rc<str>>) -> Result<(), ListenerError> {
+///         self.0.set(self.0.get() + 1);
+///         Ok(())
+///     }
+/// }
+///
+/// futures::executor::block_on(async {
+///     let clicks = Rc::new(Cell::new(0));
+///     let eventbus = LocalEventbus::new();
+///     eventbus.register("button", Clicks(clicks.clone())).await;
+///     let topic = eventbus.create_topic::<Rc<str>, _>("button").await;
+///     topic.post_message(Rc::from("ok")).await;
+///     assert_eq!(clicks.get(), 1);
+/// });
+/// ```
+#[derive(Clone)]
+pub struct LocalEventbus {
+    inner: Rc<RefCell<anymap::AnyMap>>,
+}
+
+/// A `LocalTopic` wrapper for a `TopicKey`
+pub struct LocalTopic<T> {
+    key: TopicKey,
+    bus: LocalEventbus,
+    _message: PhantomData<T>,
+}
+
+/// A `LocalEventListener` wrapper for `LocalListener`
+pub struct LocalEventListener<T> {
+    topic: TopicKey,
+    rand_id: u64,
+    bus: LocalEventbus,
+    _handler: PhantomData<T>,
+}
+
+impl LocalEventbus {
+    /// create an new local eventbus
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(anymap::AnyMap::new())),
+        }
+    }
+
+    /// create a `LocalTopic` using a topic key
+    pub async fn create_topic<T: 'static, K: Into<TopicKey>>(&self, topic_key: K) -> LocalTopic<T> {
+        LocalTopic {
+            key: topic_key.into(),
+            bus: self.clone(),
+            _message: PhantomData,
+        }
+    }
+
+    /// register a listener to eventbus
+    pub async fn register<T: 'static, K: Into<TopicKey>, L: LocalListener<T>>(
+        &self,
+        topic_key: K,
+        listener: L,
+    ) -> LocalEventListener<T> {
+        let event_listener = LocalEventListener {
+            topic: topic_key.into(),
+            rand_id: RandomIds.next_id(),
+            bus: self.clone(),
+            _handler: PhantomData,
+        };
+        trace!("add local event_listener: {:?}", event_listener);
+        self.inner
+            .borrow_mut()
+            .entry::<LocalListeners<T>>()
+            .or_insert_with(Default::default)
+            .entry(event_listener.topic.clone())
+            .or_default()
+            .insert(event_listener.rand_id, Rc::new(listener));
+        event_listener
+    }
+
+    /// unregister a listener
+    pub async fn unregister<T: 'static>(&self, event_listener: LocalEventListener<T>) {
+        let mut inner = self.inner.borrow_mut();
+        let Some(topics) = inner.get_mut::<LocalListeners<T>>() else {
+            return;
+        };
+        if let Some(listeners) = topics.get_mut(&event_listener.topic) {
+            listeners.remove(&event_listener.rand_id);
+            if listeners.is_empty() {
+                topics.remove(&event_listener.topic);
+            }
+        }
+    }
+
+    /// post an event to eventbus
+    ///
+    /// Listeners are called concurrently on the current task, they may register and unregister
+    /// listeners or post events while handling one.
+    pub async fn post<T: 'static>(&self, event: &Event<T>) {
+        trace!("recv local post [{:?}]", event.get_key());
+        let listeners: Vec<_> = match self
+            .inner
+            .borrow()
+            .get::<LocalListeners<T>>()
+            .and_then(|topics| topics.get(event.get_key()))
+        {
+            Some(listeners) => listeners.values().cloned().collect(),
+            None => {
+                trace!("no listener of topic [{}]", event.get_key());
+                return;
+            }
+        };
+        future::join_all(listeners.iter().map(|listener| async move {
+            if let Err(e) = listener.handle(event).await {
+                error!(
+                    "listener of topic [{}] failed to process event: {:?}",
+                    event.get_key(),
+                    e
+                )
+            }
+        }))
+        .await;
+    }
+}
+
+impl Default for LocalEventbus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for LocalEventbus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LocalEventbus")
+    }
+}
+
+impl<T: 'static> LocalTopic<T> {
+    /// create an event from message
+    pub fn create_event(&self, message: T) -> Event<T> {
+        Event::new(self.key.clone(), message)
+    }
+
+    /// get the key of a topic
+    pub fn get_key(&self) -> &TopicKey {
+        &self.key
+    }
+
+    /// get the associated eventbus
+    pub fn get_bus(&self) -> &LocalEventbus {
+        &self.bus
+    }
+
+    /// shorthand for post event to eventbus
+    pub async fn post(&self, event: &Event<T>) {
+        self.bus.post(event).await
+    }
+
+    /// shorthand for post message to eventbus
+    pub async fn post_message(&self, message: T) {
+        let event = self.create_event(message);
+        self.post(&event).await
+    }
+}
+
+impl<T> Debug for LocalTopic<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(format!("LocalTopic<{}>", std::any::type_name::<T>()).as_str())
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+impl<T: 'static> LocalEventListener<T> {
+    /// get the id of the listener
+    pub fn id(&self) -> u64 {
+        self.rand_id
+    }
+
+    /// get the key of the subscribed topic
+    pub fn get_key(&self) -> &TopicKey {
+        &self.topic
+    }
+
+    /// shorthand for unregister listener from eventbus
+    pub async fn unregister(self) {
+        self.bus.clone().unregister(self).await
+    }
+}
+
+impl<T> Debug for LocalEventListener<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(format!("LocalEventListener<{}>", std::any::type_name::<T>()).as_str())
+            .field("topic", &self.topic)
+            .field("rand_id", &self.rand_id)
+            .finish()
+    }
+}
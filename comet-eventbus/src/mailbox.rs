@@ -0,0 +1,231 @@
+use crate::instrument;
+use crate::shutdown::PostGuard;
+use crate::spans::{self, Span};
+use crate::{Event, PostError, TopicHandlers, TopicKey};
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::Notify;
+
+/// Policy applied when an event is posted to a full mailbox
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// wait until the mailbox has room for the event
+    #[default]
+    Wait,
+    /// drop the oldest queued event to make room for the new one
+    DropOldest,
+    /// drop the new event
+    DropNewest,
+    /// reject the new event with `PostError::QueueFull`
+    Error,
+}
+
+/// Owner of a topic mailbox, events queued before it is dropped are still delivered
+pub(crate) struct Mailbox {
+    queue: Arc<dyn ErasedQueue>,
+}
+
+/// Type erased `MailboxQueue`, so that it can be held by listeners of any message type
+trait ErasedQueue: Send + Sync {
+    fn depth(&self) -> usize;
+    fn capacity(&self) -> usize;
+    fn policy(&self) -> OverflowPolicy;
+    fn dropped(&self) -> u64;
+    fn close(&self);
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+
+/// A bounded queue of events waiting for delivery
+pub(crate) struct MailboxQueue<T> {
+    topic: TopicKey,
+    /// queued events, each one is in flight until it is delivered or dropped
+    events: Mutex<VecDeque<(Event<T>, PostGuard, Span)>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    clone: fn(&Event<T>) -> Event<T>,
+    dropped: AtomicU64,
+    closed: AtomicBool,
+    /// set while a task drains the mailbox, so that events are delivered one at a time
+    draining: AtomicBool,
+    topic_handlers: Weak<TopicHandlers>,
+    /// notified when an event is taken or the mailbox is closed
+    space: Notify,
+}
+
+impl Mailbox {
+    /// create a mailbox delivering to the topic listeners
+    ///
+    /// Queued events are drained by a task spawned on the current runtime of the eventbus,
+    /// which ends once the mailbox is empty.
+    pub(crate) fn new<T: Send + Sync + 'static>(
+        topic: TopicKey,
+        capacity: usize,
+        policy: OverflowPolicy,
+        clone: fn(&Event<T>) -> Event<T>,
+        topic_handlers: Weak<TopicHandlers>,
+    ) -> Self {
+        let queue = Arc::new(MailboxQueue {
+            topic,
+            events: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity: capacity.max(1),
+            policy,
+            clone,
+            dropped: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            draining: AtomicBool::new(false),
+            topic_handlers,
+            space: Notify::new(),
+        });
+        Self { queue }
+    }
+
+    /// typed queue of the mailbox, `None` if the message type mismatches
+    pub(crate) fn queue<T: Send + Sync + 'static>(&self) -> Option<Arc<MailboxQueue<T>>> {
+        self.queue.clone().into_any().downcast().ok()
+    }
+
+    /// number of events waiting for delivery
+    pub(crate) fn depth(&self) -> usize {
+        self.queue.depth()
+    }
+}
+
+impl Drop for Mailbox {
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
+
+impl Debug for Mailbox {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mailbox")
+            .field("depth", &self.queue.depth())
+            .field("capacity", &self.queue.capacity())
+            .field("policy", &self.queue.policy())
+            .field("dropped", &self.queue.dropped())
+            .finish()
+    }
+}
+
+impl<T: Send + Sync + 'static> MailboxQueue<T> {
+    /// queue an event, applying the overflow policy if the mailbox is full
+    pub(crate) async fn push(
+        self: &Arc<Self>,
+        event: &Event<T>,
+        guard: PostGuard,
+    ) -> Result<(), PostError> {
+        loop {
+            let mut space = pin!(self.space.notified());
+            space.as_mut().enable();
+            {
+                if self.closed.load(Ordering::Acquire) {
+                    return Err(PostError::QueueClosed(self.topic.clone()));
+                }
+                let mut events = self.events.lock().unwrap();
+                if events.len() >= self.capacity {
+                    match self.policy {
+                        OverflowPolicy::Wait => {}
+                        OverflowPolicy::DropOldest => {
+                            trace!("mailbox of topic [{}] full, drop oldest", self.topic);
+                            events.pop_front();
+                            self.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        OverflowPolicy::DropNewest => {
+                            trace!("mailbox of topic [{}] full, drop newest", self.topic);
+                            self.dropped.fetch_add(1, Ordering::Relaxed);
+                            return Ok(());
+                        }
+                        OverflowPolicy::Error => {
+                            return Err(PostError::QueueFull(self.topic.clone()));
+                        }
+                    }
+                }
+                if events.len() < self.capacity {
+                    events.push_back(((self.clone)(event), guard, Span::current()));
+                    instrument::queue_depth(&self.topic, events.len());
+                    drop(events);
+                    self.schedule();
+                    return Ok(());
+                }
+            }
+            space.await;
+        }
+    }
+
+    /// spawn a task draining the mailbox, unless one is running
+    fn schedule(self: &Arc<Self>) {
+        if self.draining.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        match self.topic_handlers.upgrade() {
+            // the runtime is read when draining starts, so that it follows `set_runtime`
+            Some(topic_handlers) => topic_handlers
+                .runtime
+                .get()
+                .spawn(Box::pin(self.clone().drain())),
+            None => self.draining.store(false, Ordering::Release),
+        }
+    }
+
+    async fn drain(self: Arc<Self>) {
+        trace!("start draining mailbox of topic [{}]", self.topic);
+        loop {
+            while let Some((event, _guard, span)) = self.pop() {
+                let Some(topic_handlers) = self.topic_handlers.upgrade() else {
+                    return;
+                };
+                // delivered in the span of the post which queued the event
+                spans::instrument(topic_handlers.notify(&event), span).await;
+            }
+            self.draining.store(false, Ordering::Release);
+            // an event queued before the flag was cleared did not spawn a task
+            if self.events.lock().unwrap().is_empty() || self.draining.swap(true, Ordering::AcqRel)
+            {
+                break;
+            }
+        }
+        trace!("stop draining mailbox of topic [{}]", self.topic);
+    }
+
+    /// take the next event, if any
+    fn pop(&self) -> Option<(Event<T>, PostGuard, Span)> {
+        let (queued, depth) = {
+            let mut events = self.events.lock().unwrap();
+            events.pop_front().map(|queued| (queued, events.len()))?
+        };
+        instrument::queue_depth(&self.topic, depth);
+        self.space.notify_one();
+        Some(queued)
+    }
+}
+
+impl<T: Send + Sync + 'static> ErasedQueue for MailboxQueue<T> {
+    fn depth(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.space.notify_waiters();
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{Event, EventListener, Eventbus, Listener, ListenerError, TopicKey};
+use async_trait::async_trait;
+use std::future::Future;
+
+/// Event listener with a native async handler, whose future is not boxed
+///
+/// `handle` can be implemented with an `async fn`. It is not object safe, so listeners of
+/// `StaticTopic`s are dispatched without allocation, while `Eventbus::register_native`
+/// boxes their futures like any `Listener`. Every `Listener` is a `NativeListener` as well.
+///
+/// ## Example:
+/// ```
+/// use comet_eventbus::{Event, ListenerError, NativeListener};
+///
+/// struct Message(u64);
+///
+/// struct Validate;
+///
+/// impl NativeListener<Message> for Validate {
+///     async fn handle(&self, event: &Event<Message>) -> Result<(), ListenerError> {
+///         assert_ne!(event.0, 0);
+///         Ok(())
+///     }
+/// }
+/// ```
+///
+/// Note: the struct which implements `NativeListener` need to be `Send` and `Sync`
+pub trait NativeListener<T>: Send + Sync + 'static {
+    /// handler callback to process event
+    fn handle<'a>(
+        &'a self,
+        event: &'a Event<T>,
+    ) -> impl Future<Output = Result<(), ListenerError>> + Send + 'a;
+
+    /// human readable name of the listener, shown by `Eventbus::topics`
+    fn name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl<T, L: Listener<T> + ?Sized> NativeListener<T> for L {
+    fn handle<'a>(
+        &'a self,
+        event: &'a Event<T>,
+    ) -> impl Future<Output = Result<(), ListenerError>> + Send + 'a {
+        Listener::handle(self, event)
+    }
+
+    fn name(&self) -> Option<&str> {
+        Listener::name(self)
+    }
+}
+
+/// Adapter of a `NativeListener` to `Listener`
+struct Native<L>(L);
+
+#[async_trait]
+impl<T: Send + Sync + 'static, L: NativeListener<T>> Listener<T> for Native<L> {
+    async fn handle(&self, event: &Event<T>) -> Result<(), ListenerError> {
+        self.0.handle(event).await
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.0.name()
+    }
+}
+
+impl Eventbus {
+    /// register a listener with a native async handler to eventbus
+    pub async fn register_native<
+        T: Send + Sync + 'static,
+        K: Into<TopicKey>,
+        L: NativeListener<T>,
+    >(
+        &self,
+        topic_key: K,
+        listener: L,
+    ) -> EventListener<T> {
+        self.register(topic_key, Native(listener)).await
+    }
+}
@@ -0,0 +1,118 @@
+use crate::consumer_group::KeyFn;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Debug, Formatter};
+use std::pin::pin;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::Notify;
+
+/// minimum number of partitions kept before pruning the released ones
+const PRUNE_THRESHOLD: usize = 64;
+
+/// Serialize delivery of events sharing the same partition key
+///
+/// Each partition is a FIFO queue of tickets. A ticket is taken while the topic is locked, so
+/// events of the same key are delivered one after another in the order they took the topic
+/// lock, while events of different keys are delivered concurrently.
+pub(crate) struct Partitioner<T> {
+    extractor: KeyFn<T>,
+    partitions: HashMap<u64, Weak<Partition>>,
+    prune_at: usize,
+}
+
+/// Queue of the events of a partition key
+#[derive(Default)]
+pub(crate) struct Partition {
+    queue: Mutex<Queue>,
+    /// notified whenever the ticket being served changes
+    turn: Notify,
+}
+
+#[derive(Default)]
+struct Queue {
+    /// number of the next ticket
+    next: u64,
+    /// number of the ticket whose event is delivered
+    serving: u64,
+    /// tickets dropped before their turn, skipped once it comes
+    abandoned: BTreeSet<u64>,
+}
+
+/// Place of an event in the queue of its partition, the next event is served once it is dropped
+pub(crate) struct Ticket {
+    partition: Arc<Partition>,
+    number: u64,
+}
+
+impl<T> Partitioner<T> {
+    pub(crate) fn new(extractor: KeyFn<T>) -> Self {
+        Self {
+            extractor,
+            partitions: HashMap::new(),
+            prune_at: PRUNE_THRESHOLD,
+        }
+    }
+
+    /// take a ticket of the partition which the message belongs to
+    pub(crate) fn ticket(&mut self, message: &T) -> Ticket {
+        let key = (self.extractor)(message);
+        let partition = match self.partitions.get(&key).and_then(Weak::upgrade) {
+            Some(partition) => partition,
+            None => {
+                if self.partitions.len() >= self.prune_at {
+                    // partitions without pending events are not referenced by any ticket
+                    self.partitions
+                        .retain(|_, partition| partition.strong_count() > 0);
+                    self.prune_at = (self.partitions.len() * 2).max(PRUNE_THRESHOLD);
+                }
+                let partition = Arc::new(Partition::default());
+                self.partitions.insert(key, Arc::downgrade(&partition));
+                partition
+            }
+        };
+        let number = {
+            let mut queue = partition.queue.lock().unwrap();
+            queue.next += 1;
+            queue.next - 1
+        };
+        Ticket { partition, number }
+    }
+}
+
+impl<T> Debug for Partitioner<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Partitioner")
+            .field("partitions", &self.partitions.len())
+            .finish()
+    }
+}
+
+impl Ticket {
+    /// wait until every event queued before this one is delivered
+    pub(crate) async fn wait(&self) {
+        loop {
+            let mut turn = pin!(self.partition.turn.notified());
+            turn.as_mut().enable();
+            if self.partition.queue.lock().unwrap().serving == self.number {
+                return;
+            }
+            turn.await;
+        }
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        let mut queue = self.partition.queue.lock().unwrap();
+        if queue.serving != self.number {
+            queue.abandoned.insert(self.number);
+            return;
+        }
+        let mut serving = self.number + 1;
+        while queue.abandoned.remove(&serving) {
+            serving += 1;
+        }
+        queue.serving = serving;
+        drop(queue);
+        self.partition.turn.notify_waiters();
+    }
+}
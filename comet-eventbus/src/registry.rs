@@ -0,0 +1,325 @@
+use crate::type_check::TypeRegistry;
+use crate::{AnyListener, EventListeners, PostError, TopicEntry, TopicInfo, TopicKey};
+use arc_swap::ArcSwapOption;
+use futures::future::{self, BoxFuture};
+use std::any::{Any, TypeId};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::hash::BuildHasher;
+use std::sync::{Arc, RwLock};
+
+/// listeners of events of any message type subscribed to a topic
+pub(crate) type AnyTopicListeners = HashMap<u64, Arc<dyn AnyListener>>;
+
+/// A `TopicKey` interned by the registry, shared by the listeners of every message type
+///
+/// `Topic` handles reach it through their `TopicEntry`, so posting through them needs no lookup.
+pub(crate) struct TopicNode {
+    key: TopicKey,
+    /// replaced as a whole on change, `None` if there is none
+    any_listeners: ArcSwapOption<AnyTopicListeners>,
+}
+
+/// Type erased `TopicEntry`, to visit the listeners of every message type
+pub(crate) trait ErasedTopic: Send + Sync {
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+
+    /// describe the topic
+    fn describe(&self, key: TopicKey) -> BoxFuture<'_, TopicInfo>;
+
+    /// check if nothing is subscribed to or configured on the topic
+    fn is_idle(&self) -> bool;
+
+    /// release the message type bound to the topic
+    fn release(&self, key: &TopicKey, types: &TypeRegistry);
+
+    /// call `on_bus_shutdown` of every listener
+    fn on_bus_shutdown<'a>(&'a self, key: &'a TopicKey) -> BoxFuture<'a, ()>;
+}
+
+impl<T: 'static> ErasedTopic for TopicEntry<T> {
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn describe(&self, key: TopicKey) -> BoxFuture<'_, TopicInfo> {
+        Box::pin(async move {
+            TopicInfo {
+                key,
+                type_name: std::any::type_name::<T>(),
+                listeners: self.lock().await.describe(),
+            }
+        })
+    }
+
+    fn is_idle(&self) -> bool {
+        self.try_lock().is_some_and(|guard| guard.is_idle())
+    }
+
+    fn release(&self, key: &TopicKey, types: &TypeRegistry) {
+        types.release::<T>(key);
+    }
+
+    fn on_bus_shutdown<'a>(&'a self, key: &'a TopicKey) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let listeners = self.lock().await.all();
+            future::join_all(
+                listeners
+                    .iter()
+                    .map(|(rand_id, listener)| listener.on_bus_shutdown(key, *rand_id)),
+            )
+            .await;
+        })
+    }
+}
+
+/// A topic key with its entries, one per message type, usually a single one
+struct Slot {
+    node: Arc<TopicNode>,
+    typed: Vec<(TypeId, Arc<dyn ErasedTopic>)>,
+}
+
+/// Listeners of every message type and topic, sharded by topic key
+///
+/// Shards are guarded by read-write locks which are only held to look an entry up, so posts
+/// to different topics never contend, and posts to the same topic only share a read lock.
+/// Every key is interned on its first use, and kept until it is pruned.
+pub(crate) struct Registry {
+    hasher: RandomState,
+    shards: Box<[RwLock<HashMap<TopicKey, Slot>>]>,
+}
+
+/// check if a topic entry can be dropped from the registry
+///
+/// The entry must be idle and not referenced elsewhere, e.g. by a `Topic` handle,
+/// so that it is never replaced while someone still holds it.
+fn is_prunable(entry: &Arc<dyn ErasedTopic>) -> bool {
+    Arc::strong_count(entry) == 1 && entry.is_idle()
+}
+
+fn downcast<T: 'static>(entry: &Arc<dyn ErasedTopic>) -> EventListeners<T> {
+    entry
+        .clone()
+        .into_any()
+        .downcast()
+        .unwrap_or_else(|_| unreachable!("entries are keyed by message type"))
+}
+
+impl TopicNode {
+    /// the interned key, clones of it share its bytes
+    pub(crate) fn key(&self) -> &TopicKey {
+        &self.key
+    }
+
+    pub(crate) fn any_listeners(&self) -> Option<Arc<AnyTopicListeners>> {
+        self.any_listeners.load_full()
+    }
+
+    /// replace the listeners of events of any message type
+    fn update_any_listeners(&self, update: impl FnOnce(&mut AnyTopicListeners)) {
+        let mut listeners = self
+            .any_listeners
+            .load()
+            .as_deref()
+            .cloned()
+            .unwrap_or_default();
+        update(&mut listeners);
+        self.any_listeners
+            .store((!listeners.is_empty()).then(|| Arc::new(listeners)));
+    }
+}
+
+impl Debug for TopicNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TopicNode").field("key", &self.key).finish()
+    }
+}
+
+impl Slot {
+    fn get<T: 'static>(&self) -> Option<EventListeners<T>> {
+        self.typed
+            .iter()
+            .find(|(type_id, _)| *type_id == TypeId::of::<T>())
+            .map(|(_, entry)| downcast(entry))
+    }
+
+    /// check if the key can be dropped along with its node
+    fn is_prunable(&self) -> bool {
+        self.typed.is_empty()
+            && self.node.any_listeners.load().is_none()
+            && Arc::strong_count(&self.node) == 1
+    }
+}
+
+impl Registry {
+    pub(crate) fn new() -> Self {
+        let shards = std::thread::available_parallelism()
+            .map_or(1, usize::from)
+            .saturating_mul(4)
+            .next_power_of_two();
+        Self {
+            hasher: RandomState::new(),
+            shards: (0..shards).map(|_| Default::default()).collect(),
+        }
+    }
+
+    fn shard(&self, key: &TopicKey) -> &RwLock<HashMap<TopicKey, Slot>> {
+        let hash = self.hasher.hash_one(key) as usize;
+        &self.shards[hash & (self.shards.len() - 1)]
+    }
+
+    /// get the slot of a key, intern the key if missing
+    fn slot(shard: &mut HashMap<TopicKey, Slot>, key: TopicKey) -> &mut Slot {
+        shard.entry(key.clone()).or_insert_with(|| Slot {
+            node: Arc::new(TopicNode {
+                key,
+                any_listeners: Default::default(),
+            }),
+            typed: Vec::new(),
+        })
+    }
+
+    /// get the listeners of a topic without creating it
+    pub(crate) fn get<T: 'static>(&self, key: &TopicKey) -> Option<EventListeners<T>> {
+        self.shard(key).read().unwrap().get(key)?.get()
+    }
+
+    /// get the listeners of a topic, create it if missing
+    ///
+    /// The key is bound to `T` along with the creation, under the lock of its shard, so that it
+    /// is never released in between. Fails if the key is bound to another type and the type
+    /// check is `TypeCheck::Deny`, the topic is not created then.
+    pub(crate) fn get_or_insert<T: 'static>(
+        &self,
+        key: TopicKey,
+        types: &TypeRegistry,
+    ) -> Result<EventListeners<T>, PostError> {
+        if let Some(listeners) = self.get(&key) {
+            return Ok(listeners);
+        }
+        let mut shard = self.shard(&key).write().unwrap();
+        let slot = Self::slot(&mut shard, key.clone());
+        if let Some(listeners) = slot.get() {
+            return Ok(listeners);
+        }
+        if let Err(e) = types.bind::<T>(&key) {
+            if slot.is_prunable() {
+                shard.remove(&key);
+            }
+            return Err(e);
+        }
+        let listeners = Arc::new(TopicEntry::<T>::new(slot.node.clone()));
+        slot.typed.push((TypeId::of::<T>(), listeners.clone()));
+        Ok(listeners)
+    }
+
+    /// listeners of a topic which are not registered, for a key bound to another type
+    pub(crate) fn detached<T: 'static>(key: TopicKey) -> EventListeners<T> {
+        Arc::new(TopicEntry::new(Arc::new(TopicNode {
+            key,
+            any_listeners: Default::default(),
+        })))
+    }
+
+    /// get the interned node of a topic key
+    pub(crate) fn node(&self, key: &TopicKey) -> Option<Arc<TopicNode>> {
+        let shard = self.shard(key).read().unwrap();
+        shard.get(key).map(|slot| slot.node.clone())
+    }
+
+    /// add a listener of events of any message type
+    pub(crate) fn add_any(&self, key: TopicKey, rand_id: u64, listener: Arc<dyn AnyListener>) {
+        let mut shard = self.shard(&key).write().unwrap();
+        Self::slot(&mut shard, key)
+            .node
+            .update_any_listeners(|listeners| {
+                listeners.insert(rand_id, listener);
+            });
+    }
+
+    /// remove a listener of events of any message type, drop the topic if it is prunable
+    pub(crate) fn remove_any(&self, key: &TopicKey, rand_id: u64) {
+        let mut shard = self.shard(key).write().unwrap();
+        let Some(slot) = shard.get(key) else {
+            return;
+        };
+        slot.node.update_any_listeners(|listeners| {
+            listeners.remove(&rand_id);
+        });
+        if slot.is_prunable() {
+            shard.remove(key);
+        }
+    }
+
+    /// drop the topic if it is prunable, returns `true` if it is dropped
+    pub(crate) fn remove_if_prunable<T: 'static>(
+        &self,
+        key: &TopicKey,
+        types: &TypeRegistry,
+    ) -> bool {
+        let mut shard = self.shard(key).write().unwrap();
+        let Some(slot) = shard.get_mut(key) else {
+            return false;
+        };
+        let Some(index) = slot
+            .typed
+            .iter()
+            .position(|(type_id, entry)| *type_id == TypeId::of::<T>() && is_prunable(entry))
+        else {
+            return false;
+        };
+        drop(slot.typed.swap_remove(index));
+        if slot.is_prunable() {
+            shard.remove(key);
+        }
+        types.release::<T>(key);
+        true
+    }
+
+    /// every topic of every message type
+    pub(crate) fn entries(&self) -> Vec<(TopicKey, Arc<dyn ErasedTopic>)> {
+        self.shards
+            .iter()
+            .flat_map(|shard| {
+                let shard = shard.read().unwrap();
+                shard
+                    .iter()
+                    .flat_map(|(key, slot)| {
+                        slot.typed
+                            .iter()
+                            .map(|(_, entry)| (key.clone(), entry.clone()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// drop the idle topics, returns the number of dropped topics
+    pub(crate) fn prune(&self, types: &TypeRegistry) -> usize {
+        let mut pruned = 0;
+        for shard in self.shards.iter() {
+            let mut shard = shard.write().unwrap();
+            shard.retain(|key, slot| {
+                slot.typed.retain(|(_, entry)| {
+                    let prunable = is_prunable(entry);
+                    if prunable {
+                        entry.release(key, types);
+                        pruned += 1;
+                    }
+                    !prunable
+                });
+                !slot.is_prunable()
+            });
+        }
+        pruned
+    }
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Registry")
+            .field("shards", &self.shards.len())
+            .finish()
+    }
+}
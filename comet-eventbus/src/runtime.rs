@@ -0,0 +1,124 @@
+use futures::future::BoxFuture;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Async runtime used by an `Eventbus` to spawn tasks and to wait
+///
+/// The core of the eventbus only relies on executor agnostic primitives, the runtime is needed
+/// to drain the mailboxes of queued topics and to enforce timeouts.
+///
+/// ## Example:
+/// ```
+/// use comet_eventbus::Runtime;
+/// use futures::future::BoxFuture;
+/// use std::time::Duration;
+///
+/// struct Threads;
+///
+/// impl Runtime for Threads {
+///     fn spawn(&self, future: BoxFuture<'static, ()>) {
+///         std::thread::spawn(move || futures::executor::block_on(future));
+///     }
+///
+///     fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+///         Box::pin(futures_timer::Delay::new(duration))
+///     }
+/// }
+/// ```
+pub trait Runtime: Send + Sync + 'static {
+    /// run a future in the background
+    fn spawn(&self, future: BoxFuture<'static, ()>);
+
+    /// a future which completes after `duration`
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// Runtime backed by the current tokio runtime
+///
+/// Outside of a tokio runtime, e.g. when posting with `Eventbus::post_blocking` from a plain
+/// thread, it falls back to `ThreadRuntime`.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioRuntime;
+
+#[cfg(feature = "tokio")]
+impl Runtime for TokioRuntime {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(future);
+            }
+            Err(_) => ThreadRuntime.spawn(future),
+        }
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        match tokio::runtime::Handle::try_current() {
+            Ok(_) => Box::pin(tokio::time::sleep(duration)),
+            Err(_) => ThreadRuntime.sleep(duration),
+        }
+    }
+}
+
+/// Runtime which needs no executor, each task runs on a dedicated thread
+/// and timers run on the global timer thread of `futures-timer`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadRuntime;
+
+impl Runtime for ThreadRuntime {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        std::thread::spawn(move || futures::executor::block_on(future));
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(futures_timer::Delay::new(duration))
+    }
+}
+
+/// Runtime slot of an `Eventbus`, `TokioRuntime` by default if the `tokio` feature is enabled,
+/// otherwise `ThreadRuntime`
+///
+/// Clones share the slot, so they see the runtime set afterwards.
+#[derive(Clone)]
+pub(crate) struct RuntimeSlot(Arc<RwLock<Arc<dyn Runtime>>>);
+
+impl RuntimeSlot {
+    pub(crate) fn set(&self, runtime: Arc<dyn Runtime>) {
+        *self.0.write().unwrap() = runtime;
+    }
+
+    pub(crate) fn get(&self) -> Arc<dyn Runtime> {
+        self.0.read().unwrap().clone()
+    }
+}
+
+impl Default for RuntimeSlot {
+    fn default() -> Self {
+        #[cfg(feature = "tokio")]
+        return Self(Arc::new(RwLock::new(Arc::new(TokioRuntime))));
+        #[cfg(not(feature = "tokio"))]
+        Self(Arc::new(RwLock::new(Arc::new(ThreadRuntime))))
+    }
+}
+
+impl Debug for RuntimeSlot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RuntimeSlot")
+    }
+}
+
+/// run a future for at most `duration`, `None` if it did not finish in time
+pub(crate) async fn timeout<F: Future>(
+    runtime: &dyn Runtime,
+    duration: Duration,
+    future: F,
+) -> Option<F::Output> {
+    let future = std::pin::pin!(future);
+    match futures::future::select(future, runtime.sleep(duration)).await {
+        futures::future::Either::Left((output, _)) => Some(output),
+        futures::future::Either::Right(_) => None,
+    }
+}
@@ -0,0 +1,66 @@
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+/// Track posts in flight, so that shutdown can wait for them
+#[derive(Debug, Default)]
+pub(crate) struct Lifecycle {
+    closed: CancellationToken,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+/// A post accepted by the eventbus, it is in flight until the guard is dropped
+#[derive(Debug)]
+pub(crate) struct PostGuard(Arc<Lifecycle>);
+
+impl Lifecycle {
+    /// accept a post, returns `None` if the eventbus is shut down
+    pub(crate) fn enter(self: &Arc<Self>) -> Option<PostGuard> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = PostGuard(self.clone());
+        if self.closed.is_cancelled() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// reject new posts
+    pub(crate) fn close(&self) {
+        self.closed.cancel();
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.is_cancelled()
+    }
+
+    pub(crate) async fn closed(&self) {
+        self.closed.cancelled().await
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// wait until there is no post in flight
+    pub(crate) async fn idle(&self) {
+        loop {
+            let mut idle = pin!(self.idle.notified());
+            idle.as_mut().enable();
+            if self.in_flight() == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+}
+
+impl Drop for PostGuard {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
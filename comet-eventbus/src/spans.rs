@@ -0,0 +1,98 @@
+#![cfg_attr(
+    not(feature = "tracing"),
+    allow(unused_variables, clippy::extra_unused_type_parameters)
+)]
+
+use crate::TopicKey;
+#[cfg(feature = "otel")]
+use std::collections::HashMap;
+use std::future::Future;
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::Span;
+
+/// Placeholder of `tracing::Span` when the `tracing` feature is disabled
+#[cfg(not(feature = "tracing"))]
+#[derive(Debug, Clone)]
+pub(crate) struct Span;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    pub(crate) fn current() -> Self {
+        Self
+    }
+}
+
+/// span of posting an event
+pub(crate) fn post<T>(topic: &TopicKey) -> Span {
+    #[cfg(feature = "tracing")]
+    return tracing::info_span!(
+        "post",
+        topic = %topic,
+        r#type = std::any::type_name::<T>()
+    );
+    #[cfg(not(feature = "tracing"))]
+    Span
+}
+
+/// span of a listener handling an event
+pub(crate) fn listener<T>(topic: &TopicKey) -> Span {
+    #[cfg(feature = "tracing")]
+    return tracing::info_span!(
+        "listener",
+        topic = %topic,
+        r#type = std::any::type_name::<T>()
+    );
+    #[cfg(not(feature = "tracing"))]
+    Span
+}
+
+/// run a future in a span
+pub(crate) fn instrument<F: Future>(future: F, span: Span) -> impl Future<Output = F::Output> {
+    #[cfg(feature = "tracing")]
+    return tracing::Instrument::instrument(future, span);
+    #[cfg(not(feature = "tracing"))]
+    future
+}
+
+/// W3C trace context of the current span, to be sent along with a bridged event
+///
+/// It is encoded by the global propagator of `opentelemetry`, which should be set by the
+/// application, e.g. to a `TraceContextPropagator`.
+#[cfg(feature = "otel")]
+pub(crate) fn inject() -> HashMap<String, String> {
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let context = Span::current().context();
+    let mut metadata = HashMap::new();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut metadata)
+    });
+    metadata
+}
+
+/// span of receiving a bridged event, child of the trace context sent along with it
+#[cfg(feature = "bridge")]
+#[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+pub(crate) fn received(
+    topic: &TopicKey,
+    metadata: &std::collections::HashMap<String, String>,
+) -> Span {
+    #[cfg(feature = "otel")]
+    {
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let span = tracing::info_span!("bridge.receive", topic = %topic);
+        let context = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(metadata)
+        });
+        if let Err(e) = span.set_parent(context) {
+            trace!("failed to set remote parent of span: {}", e);
+        }
+        span
+    }
+    #[cfg(all(feature = "tracing", not(feature = "otel")))]
+    return tracing::info_span!("bridge.receive", topic = %topic);
+    #[cfg(not(feature = "tracing"))]
+    Span
+}
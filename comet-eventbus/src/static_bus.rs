@@ -0,0 +1,312 @@
+use crate::blocking::{block_on, BlockingListener};
+use crate::{listener_panicked, Event, Eventbus, Listener, ListenerError, Topic, TopicKey};
+use crate::{ErrorHandler, ListenerFailure, NativeListener, PostError, SyncListener};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::any::type_name;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::panic::AssertUnwindSafe;
+
+/// Declare an eventbus whose topics and message types are known at compile time
+///
+/// It generates a struct with a `StaticTopic` field per topic, keyed by the field name.
+/// Posting to a field calls its listeners directly, without any lookup. Listeners of a topic
+/// are of the type given after `=>`, which dispatches statically, or any `Listener` boxed.
+/// A `NativeListener` given after `=>` is called without allocation.
+///
+/// ## Example:
+/// ```
+/// use comet_eventbus::{async_trait, static_bus, Event, Eventbus, Listener, ListenerError};
+///
+/// struct Order(u64);
+/// struct Click;
+///
+/// struct Validate;
+///
+/// #[async_trait]
+/// impl Listener<Order> for Validate {
+///     async fn handle(&self, event: &Event<Order>) -> Result<(), ListenerError> {
+///         assert_ne!(event.0, 0);
+///         Ok(())
+///     }
+/// }
+///
+/// static_bus! {
+///     /// events of the checkout
+///     pub struct Checkout {
+///         /// placed orders
+///         orders: Order => Validate,
+///         /// clicks of the checkout button
+///         clicks: Click,
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut checkout = Checkout::new();
+/// checkout.orders.register(Validate);
+///
+/// // forward events to a dynamic eventbus, after the listeners of the static one
+/// let eventbus = Eventbus::new();
+/// checkout.forward_to(&eventbus);
+/// checkout.orders.post_message(Order(1)).await.unwrap();
+/// # }
+/// ```
+///
+/// Blocking code registers `SyncListener`s and posts without any executor:
+/// ```
+/// use comet_eventbus::{static_bus, Event, ListenerError, SyncListener};
+///
+/// struct Reading(u16);
+///
+/// struct Threshold;
+///
+/// impl SyncListener<Reading> for Threshold {
+///     fn handle(&self, event: &Event<Reading>) -> Result<(), ListenerError> {
+///         assert!(event.0 < 1024);
+///         Ok(())
+///     }
+/// }
+///
+/// static_bus! {
+///     struct Sensors {
+///         readings: Reading,
+///     }
+/// }
+///
+/// let mut sensors = Sensors::new();
+/// sensors.readings.register_sync(Threshold);
+/// sensors.readings.post_message_blocking(Reading(42)).unwrap();
+/// ```
+#[macro_export]
+macro_rules! static_bus {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$topic_meta:meta])*
+                $topic:ident: $message:ty $(=> $listener:ty)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$topic_meta])*
+                $vis $topic: $crate::StaticTopic<$message $(, $listener)?>,
+            )*
+        }
+
+        impl $name {
+            /// create the eventbus, topics are keyed by their field names
+            $vis fn new() -> Self {
+                Self {
+                    $($topic: $crate::StaticTopic::new(::core::stringify!($topic)),)*
+                }
+            }
+
+            /// forward the events of every topic to a dynamic `Eventbus`
+            $vis fn forward_to(&mut self, eventbus: &$crate::Eventbus) {
+                $(self.$topic.forward_to(eventbus);)*
+            }
+        }
+
+        impl ::core::default::Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+/// A topic of an eventbus declared by `static_bus!`
+///
+/// Listeners are called one after another in the order they are registered, then the event is
+/// forwarded to the dynamic `Eventbus` if any. Lifecycle hooks of listeners are not called.
+///
+/// Failures of listeners, panics included, go to the `ErrorHandler` of the topic, else to the
+/// handlers of the forwarded eventbus, else they are logged. The `listener_id` of a failure is
+/// the position of the listener in the topic.
+pub struct StaticTopic<T, L = Box<dyn Listener<T>>> {
+    key: TopicKey,
+    listeners: Vec<L>,
+    forward: Option<Topic<T>>,
+    error_handler: Option<Box<dyn ErrorHandler>>,
+    _message: PhantomData<fn(&T)>,
+}
+
+impl<T, L> StaticTopic<T, L> {
+    /// create a topic without listeners
+    pub fn new<K: Into<TopicKey>>(topic_key: K) -> Self {
+        Self {
+            key: topic_key.into(),
+            listeners: Vec::new(),
+            forward: None,
+            error_handler: None,
+            _message: PhantomData,
+        }
+    }
+
+    /// create an event from message
+    pub fn create_event(&self, message: T) -> Event<T> {
+        Event::new(self.key.clone(), message)
+    }
+
+    /// get the key of a topic
+    pub fn get_key(&self) -> &TopicKey {
+        &self.key
+    }
+
+    /// get the listeners of the topic
+    pub fn listeners(&self) -> &[L] {
+        &self.listeners
+    }
+
+    /// set the `ErrorHandler` of listener failures on the topic, replacing the previous one
+    pub fn set_error_handler<H: ErrorHandler>(&mut self, handler: H) {
+        self.error_handler = Some(Box::new(handler));
+    }
+
+    /// remove the `ErrorHandler` of the topic
+    pub fn clear_error_handler(&mut self) {
+        self.error_handler = None;
+    }
+
+    /// forward events to the same topic of a dynamic `Eventbus`
+    ///
+    /// With `TypeCheck::Deny`, if the topic is bound to another message type, the mismatch is
+    /// logged and posts fail with `PostError::TypeMismatch` once the listeners are called.
+    pub fn forward_to(&mut self, eventbus: &Eventbus)
+    where
+        T: 'static,
+    {
+        self.forward = Some(eventbus.resolve_topic(self.key.clone()));
+    }
+}
+
+impl<T: 'static, L: NativeListener<T>> StaticTopic<T, L> {
+    /// register a listener to the topic
+    pub fn register(&mut self, listener: L) {
+        self.listeners.push(listener);
+    }
+}
+
+impl<T: Send + Sync + 'static> StaticTopic<T> {
+    /// register a listener of any type to the topic
+    pub fn register_boxed<L: Listener<T>>(&mut self, listener: L) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// register a blocking listener to the topic
+    pub fn register_sync<L: SyncListener<T>>(&mut self, listener: L) {
+        self.listeners.push(Box::new(BlockingListener(listener)));
+    }
+}
+
+impl<T: Send + Sync + 'static, L: NativeListener<T>> StaticTopic<T, L> {
+    /// post an event to the listeners of the topic
+    ///
+    /// # Errors
+    /// Posting fails if the event is forwarded and the dynamic eventbus rejects it.
+    pub async fn post(&self, event: &Event<T>) -> Result<(), PostError> {
+        for (position, listener) in self.listeners.iter().enumerate() {
+            let result = AssertUnwindSafe(listener.handle(event))
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|payload| Err(listener_panicked(payload)));
+            if let Err(e) = result {
+                self.report(position, &e);
+            }
+        }
+        match &self.forward {
+            Some(topic) => topic.post(event).await,
+            None => Ok(()),
+        }
+    }
+
+    /// shorthand for post message to the topic
+    pub async fn post_message(&self, message: T) -> Result<(), PostError> {
+        let event = self.create_event(message);
+        self.post(&event).await
+    }
+
+    /// post an event to the listeners of the topic from non-async code
+    ///
+    /// Must not be called from an async context, as it blocks the current thread.
+    ///
+    /// # Errors
+    /// Posting fails if the event is forwarded and the dynamic eventbus rejects it.
+    pub fn post_blocking(&self, event: &Event<T>) -> Result<(), PostError> {
+        block_on(self.post(event))
+    }
+
+    /// shorthand for post message to the topic from non-async code
+    pub fn post_message_blocking(&self, message: T) -> Result<(), PostError> {
+        let event = self.create_event(message);
+        self.post_blocking(&event)
+    }
+}
+
+impl<T, L> StaticTopic<T, L> {
+    fn report(&self, position: usize, error: &ListenerError) {
+        let failure = ListenerFailure {
+            topic: &self.key,
+            type_name: type_name::<T>(),
+            listener_id: position as u64,
+            group: None,
+            error,
+        };
+        match (&self.error_handler, &self.forward) {
+            (Some(handler), _) => handler.on_error(&failure),
+            (None, Some(topic)) => topic.bus.inner.topic_handlers.errors.report(&failure),
+            (None, None) => error!(
+                "listener {} of topic [{}] failed to process event: {:?}",
+                position, self.key, error
+            ),
+        }
+    }
+}
+
+impl<T, L> Debug for StaticTopic<T, L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(format!("StaticTopic<{}>", std::any::type_name::<T>()).as_str())
+            .field("key", &self.key)
+            .field("listeners", &self.listeners.len())
+            .field("forward", &self.forward.is_some())
+            .field("error_handler", &self.error_handler.is_some())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl<T: Send + Sync + 'static> Listener<T> for Box<dyn Listener<T>> {
+    // returns the future of the boxed listener as is, instead of boxing it again
+    fn handle<'a, 'b, 'async_trait>(
+        &'a self,
+        event: &'b Event<T>,
+    ) -> BoxFuture<'async_trait, Result<(), ListenerError>>
+    where
+        'a: 'async_trait,
+        'b: 'async_trait,
+    {
+        (**self).handle(event)
+    }
+
+    fn name(&self) -> Option<&str> {
+        (**self).name()
+    }
+
+    async fn on_register(&self, topic: &TopicKey, listener_id: u64) {
+        (**self).on_register(topic, listener_id).await
+    }
+
+    async fn on_unregister(&self, topic: &TopicKey, listener_id: u64) {
+        (**self).on_unregister(topic, listener_id).await
+    }
+
+    async fn on_bus_shutdown(&self, topic: &TopicKey, listener_id: u64) {
+        (**self).on_bus_shutdown(topic, listener_id).await
+    }
+}
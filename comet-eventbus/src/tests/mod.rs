@@ -1,4 +1,12 @@
-#[cfg(all(feature = "async", feature = "bridge"))]
+#[cfg(feature = "tokio")]
 mod test_async;
-#[cfg(feature = "sync")]
+#[cfg(feature = "bridge")]
+mod test_bridge;
+#[cfg(feature = "tokio")]
+mod test_local;
+#[cfg(all(feature = "tokio", feature = "metrics"))]
+mod test_metrics;
+mod test_runtime;
 mod test_sync;
+#[cfg(all(feature = "tokio", feature = "tracing"))]
+mod test_tracing;
@@ -1,26 +1,21 @@
-use crate::bridge::EventbusBridge;
 use crate::*;
-use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
-struct HandlerA;
-struct HandlerB;
+struct Handler;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
 struct Message {
     id: u8,
 }
 
 #[async_trait::async_trait]
-impl Listener<Message> for HandlerA {
-    async fn handle(&self, event: &Event<Message>) {
-        println!("A: {:?}", event)
-    }
-}
-#[async_trait::async_trait]
-impl Listener<Message> for HandlerB {
-    async fn handle(&self, event: &Event<Message>) {
-        println!("B: {:?}", event)
+impl Listener<Message> for Handler {
+    async fn handle(&self, event: &Event<Message>) -> Result<(), ListenerError> {
+        println!("{:?}", event);
+        assert_ne!(event.id, 2);
+        Ok(())
     }
 }
 
@@ -28,52 +23,1352 @@ impl Listener<Message> for HandlerB {
 async fn test() {
     let eventbus = Eventbus::new();
     let topic = TopicKey::from("foobar");
-    let handler = eventbus.register(topic.clone(), HandlerA).await;
+    let handler = eventbus.register(topic.clone(), Handler).await;
     let topic = eventbus.create_topic(topic.clone()).await;
     let event = Event::new(topic.key.clone(), Message { id: 1 });
-    topic.post(&event).await;
+    topic.post(&event).await.unwrap();
     handler.unregister().await;
     let event = Event::new(topic.key.clone(), Message { id: 2 });
     // this should not produce any output since we already unregister listener
-    topic.post(&event).await;
+    topic.post(&event).await.unwrap();
+}
+
+struct Counter(Arc<AtomicUsize>);
+
+#[async_trait::async_trait]
+impl Listener<Message> for Counter {
+    async fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
 }
 
 #[tokio::test]
-async fn test_bridge() {
-    let eventbus_a = Eventbus::new();
-    let eventbus_b = Eventbus::new();
-    let bridged_a = EventbusBridge::new(eventbus_a);
-    let bridged_b = EventbusBridge::new(eventbus_b);
+async fn test_consumer_group() {
+    let eventbus = Eventbus::new();
+    let topic = TopicKey::from("jobs");
+    let counters: Vec<_> = (0..3).map(|_| Arc::new(AtomicUsize::new(0))).collect();
+    for counter in counters.iter() {
+        eventbus
+            .register_group(topic.clone(), "workers", Counter(counter.clone()))
+            .await;
+    }
+    let broadcast = Arc::new(AtomicUsize::new(0));
+    eventbus
+        .register(topic.clone(), Counter(broadcast.clone()))
+        .await;
 
-    let server_a = bridged_a.clone().listen("127.0.0.1:50001".parse().unwrap());
-    tokio::spawn(server_a);
-    let server_b = bridged_b.clone().listen("127.0.0.1:50002".parse().unwrap());
-    tokio::spawn(server_b);
+    let topic = eventbus.create_topic(topic).await;
+    for id in 0..6 {
+        topic.post_message(Message { id }).await.unwrap();
+    }
+    // every member of the group shares the load
+    for counter in counters.iter() {
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+    assert_eq!(broadcast.load(Ordering::SeqCst), 6);
+}
 
-    tokio::time::sleep(Duration::from_secs(5)).await;
-    bridged_a.connect("http://127.0.0.1:50002").await.unwrap();
-    bridged_b.connect("http://127.0.0.1:50001").await.unwrap();
+#[tokio::test]
+async fn test_consumer_group_key_hash() {
+    let eventbus = Eventbus::new();
+    let topic = TopicKey::from("jobs");
+    let counters: Vec<_> = (0..3).map(|_| Arc::new(AtomicUsize::new(0))).collect();
+    for counter in counters.iter() {
+        eventbus
+            .register_group(topic.clone(), "workers", Counter(counter.clone()))
+            .await;
+    }
+    eventbus
+        .set_group_strategy(
+            topic.clone(),
+            "workers",
+            GroupStrategy::key_hash(|message: &Message| message.id),
+        )
+        .await;
 
-    let topic = TopicKey::from("foobar");
+    let topic = eventbus.create_topic(topic).await;
+    for _ in 0..6 {
+        topic.post_message(Message { id: 1 }).await.unwrap();
+    }
+    // the same key always goes to the same member
+    let mut counts: Vec<_> = counters.iter().map(|c| c.load(Ordering::SeqCst)).collect();
+    counts.sort();
+    assert_eq!(counts, vec![0, 0, 6]);
+}
+
+#[derive(Debug)]
+struct Order {
+    customer: u8,
+    seq: u8,
+}
+
+#[derive(Default)]
+struct OrderRecorder {
+    handled: std::sync::Mutex<Vec<(u8, u8)>>,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl Listener<Order> for Arc<OrderRecorder> {
+    async fn handle(&self, event: &Event<Order>) -> Result<(), ListenerError> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        // later events finish faster, they would overtake earlier ones without ordering
+        tokio::time::sleep(Duration::from_millis(10 * (5 - event.seq as u64))).await;
+        self.handled
+            .lock()
+            .unwrap()
+            .push((event.customer, event.seq));
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_partition_key() {
+    let eventbus = Eventbus::new();
+    let topic = TopicKey::from("orders");
+    let recorder = Arc::new(OrderRecorder::default());
+    eventbus.register(topic.clone(), recorder.clone()).await;
+    eventbus
+        .set_partition_key(topic.clone(), |order: &Order| order.customer)
+        .await;
+
+    let topic = eventbus.create_topic(topic).await;
+    futures::future::join_all((0..5).flat_map(|seq| {
+        let topic = &topic;
+        (0..2).map(move |customer| topic.post_message(Order { customer, seq }))
+    }))
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+
+    let handled = recorder.handled.lock().unwrap();
+    for customer in 0..2 {
+        let seqs: Vec<_> = handled
+            .iter()
+            .filter(|(c, _)| *c == customer)
+            .map(|(_, seq)| *seq)
+            .collect();
+        assert_eq!(seqs, vec![0, 1, 2, 3, 4]);
+    }
+    // different keys are still delivered concurrently
+    assert_eq!(recorder.max_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[derive(Debug)]
+struct Ticketed {
+    key: u8,
+    poster: u8,
+    seq: u8,
+}
+
+#[derive(Default)]
+struct TicketRecorder(std::sync::Mutex<Vec<(u8, u8, u8)>>);
 
-    let handler_a = bridged_a.register(topic.clone(), HandlerA).await;
-    let handler_b = bridged_b.register(topic.clone(), HandlerB).await;
+#[async_trait::async_trait]
+impl Listener<Ticketed> for Arc<TicketRecorder> {
+    async fn handle(&self, event: &Event<Ticketed>) -> Result<(), ListenerError> {
+        for _ in 0..event.seq % 3 {
+            tokio::task::yield_now().await;
+        }
+        self.0
+            .lock()
+            .unwrap()
+            .push((event.key, event.poster, event.seq));
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_partition_key_threads() {
+    let eventbus = Eventbus::new();
+    let recorder = Arc::new(TicketRecorder::default());
+    eventbus.register("orders", recorder.clone()).await;
+    eventbus
+        .set_partition_key("orders", |event: &Ticketed| event.key)
+        .await;
 
-    let topic_a = bridged_a.create_topic(topic.clone()).await;
-    let event = Event::new(topic_a.get_key().clone(), Message { id: 1 });
-    topic_a.post(&event).await.unwrap();
+    // posters on separate tasks contend for the same keys
+    let posters = (0..4).map(|poster| {
+        let eventbus = eventbus.clone();
+        tokio::spawn(async move {
+            let topic = eventbus.create_topic("orders").await;
+            futures::future::join_all((0..50).map(|seq| {
+                topic.post_message(Ticketed {
+                    key: seq % 2,
+                    poster,
+                    seq,
+                })
+            }))
+            .await
+        })
+    });
+    for results in futures::future::join_all(posters).await {
+        assert!(results.unwrap().iter().all(Result::is_ok));
+    }
 
-    let topic_b = bridged_b.create_topic(topic.clone()).await;
-    let event = Event::new(topic_b.get_key().clone(), Message { id: 2 });
-    topic_b.post(&event).await.unwrap();
+    let handled = recorder.0.lock().unwrap();
+    assert_eq!(handled.len(), 200);
+    for key in 0..2 {
+        for poster in 0..4 {
+            let seqs: Vec<_> = handled
+                .iter()
+                .filter(|(k, p, _)| (*k, *p) == (key, poster))
+                .map(|(_, _, seq)| *seq)
+                .collect();
+            assert!(seqs.windows(2).all(|w| w[0] < w[1]), "{:?}", seqs);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_partition_key_cancelled() {
+    let eventbus = Eventbus::new();
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let handled = Arc::new(AtomicUsize::new(0));
+    eventbus
+        .register(
+            "jobs",
+            Gated {
+                gate: gate.clone(),
+                handled: handled.clone(),
+            },
+        )
+        .await;
+    eventbus.set_partition_key("jobs", |_: &Job| 0).await;
+    let topic = eventbus.create_topic("jobs").await;
+
+    let blocked = topic.post_message(Job);
+    // waits behind the blocked event, then gives up
+    let cancelled = tokio::time::timeout(Duration::from_millis(20), async {
+        futures::future::join(blocked, topic.post_message(Job)).await
+    });
+    assert!(cancelled.await.is_err());
+    // the tickets of cancelled posts are skipped
+    gate.add_permits(1);
+    topic.post_message(Job).await.unwrap();
+    assert_eq!(handled.load(Ordering::SeqCst), 1);
+}
+
+struct Gated {
+    gate: Arc<tokio::sync::Semaphore>,
+    handled: Arc<AtomicUsize>,
+}
+
+#[derive(Debug, Clone)]
+struct Job;
+
+#[async_trait::async_trait]
+impl Listener<Job> for Gated {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        self.gate.acquire().await.unwrap().forget();
+        self.handled.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_queued_topic() {
+    let eventbus = Eventbus::new();
+    let topic = TopicKey::from("queued");
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let handled = Arc::new(AtomicUsize::new(0));
+    let listener = Gated {
+        gate: gate.clone(),
+        handled: handled.clone(),
+    };
+    eventbus
+        .register::<Job, _, _>(topic.clone(), listener)
+        .await;
+    eventbus
+        .enable_queue::<Job, _>(topic.clone(), 2, OverflowPolicy::Error)
+        .await;
+
+    let topic = eventbus.create_topic(topic).await;
+    // post returns even though the listener is blocked
+    topic.post_message(Job).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    topic.post_message(Job).await.unwrap();
+    topic.post_message(Job).await.unwrap();
+    assert_eq!(
+        eventbus
+            .queue_depth::<Job, _>(topic.get_key().clone())
+            .await,
+        Some(2)
+    );
+    assert!(matches!(
+        topic.post_message(Job).await,
+        Err(PostError::QueueFull(_))
+    ));
+
+    gate.add_permits(3);
+    while handled.load(Ordering::SeqCst) < 3 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    assert_eq!(
+        eventbus
+            .queue_depth::<Job, _>(topic.get_key().clone())
+            .await,
+        Some(0)
+    );
+}
+
+#[tokio::test]
+async fn test_max_concurrency() {
+    let eventbus = Eventbus::new();
+    let topic = TopicKey::from("orders");
+    let recorder = Arc::new(OrderRecorder::default());
+    eventbus
+        .register_with(
+            topic.clone(),
+            recorder.clone(),
+            ListenerOptions::new().max_concurrency(1),
+        )
+        .await;
+
+    let topic = eventbus.create_topic(topic).await;
+    futures::future::join_all(
+        (0..3).map(|customer| topic.post_message(Order { customer, seq: 4 })),
+    )
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    assert_eq!(recorder.handled.lock().unwrap().len(), 3);
+    assert_eq!(recorder.max_in_flight.load(Ordering::SeqCst), 1);
+}
+
+struct Sleeper(Arc<AtomicUsize>);
+
+#[async_trait::async_trait]
+impl Listener<Message> for Sleeper {
+    async fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
 
-    handler_a.unregister().await;
-    let topic_b = bridged_b.create_topic(topic.clone()).await;
-    let event = Event::new(topic_b.get_key().clone(), Message { id: 2 });
-    topic_b.post(&event).await.unwrap();
+#[tokio::test]
+async fn test_timeout() {
+    let eventbus = Eventbus::new();
+    let topic = TopicKey::from("slow");
+    let finished = Arc::new(AtomicUsize::new(0));
+    eventbus
+        .register_with(
+            topic.clone(),
+            Sleeper(finished.clone()),
+            ListenerOptions::new().timeout(Duration::from_millis(10)),
+        )
+        .await;
+
+    let topic = eventbus.create_topic(topic).await;
+    let start = std::time::Instant::now();
+    topic.post_message(Message { id: 1 }).await.unwrap();
+    assert!(start.elapsed() < Duration::from_millis(50));
+    // the timed out invocation is cancelled and never finishes
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(finished.load(Ordering::SeqCst), 0);
+}
+
+struct Cooperative {
+    listener_id: Arc<AtomicU64>,
+    cancelled: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl ContextListener<Message> for Cooperative {
+    async fn handle(&self, _: &Event<Message>, cx: &ListenerContext) -> Result<(), ListenerError> {
+        self.listener_id.store(cx.listener_id(), Ordering::SeqCst);
+        let token = cx.token().clone();
+        let cancelled = self.cancelled.clone();
+        // work handed off to another task still observes the cancellation
+        tokio::spawn(async move {
+            token.cancelled().await;
+            cancelled.fetch_add(1, Ordering::SeqCst);
+        });
+        cx.cancelled().await;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_context_cancelled_by_unregister() {
+    let eventbus = Eventbus::new();
+    let topic = TopicKey::from("long");
+    let listener_id = Arc::new(AtomicU64::new(0));
+    let cancelled = Arc::new(AtomicUsize::new(0));
+    let listener = Cooperative {
+        listener_id: listener_id.clone(),
+        cancelled: cancelled.clone(),
+    };
+    let listener = eventbus
+        .register_context(topic.clone(), listener, ListenerOptions::new())
+        .await;
+    let id = listener.id();
+
+    let topic = eventbus.create_topic(topic).await;
+    let post = tokio::spawn(async move { topic.post_message(Message { id: 1 }).await });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!post.is_finished());
+    listener.unregister().await;
+    post.await.unwrap().unwrap();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(listener_id.load(Ordering::SeqCst), id);
+    assert_eq!(cancelled.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_context_cancelled_by_timeout() {
+    let eventbus = Eventbus::new();
+    let topic = TopicKey::from("long");
+    let cancelled = Arc::new(AtomicUsize::new(0));
+    let listener = Cooperative {
+        listener_id: Default::default(),
+        cancelled: cancelled.clone(),
+    };
+    let options = ListenerOptions::new().timeout(Duration::from_millis(10));
+    eventbus
+        .register_context(topic.clone(), listener, options)
+        .await;
+
+    let topic = eventbus.create_topic(topic).await;
+    topic.post_message(Message { id: 1 }).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(cancelled.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_shutdown() {
+    let eventbus = Eventbus::new();
+    let topic = TopicKey::from("queued");
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let handled = Arc::new(AtomicUsize::new(0));
+    let listener = Gated {
+        gate: gate.clone(),
+        handled: handled.clone(),
+    };
+    eventbus
+        .register::<Job, _, _>(topic.clone(), listener)
+        .await;
+    eventbus
+        .enable_queue::<Job, _>(topic.clone(), 4, OverflowPolicy::Wait)
+        .await;
+    let topic = eventbus.create_topic(topic).await;
+    topic.post_message(Job).await.unwrap();
+    topic.post_message(Job).await.unwrap();
+
+    let shutdown = tokio::spawn({
+        let eventbus = eventbus.clone();
+        async move { eventbus.shutdown(Duration::from_secs(1)).await }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(eventbus.is_shutdown());
+    assert!(matches!(
+        topic.post_message(Job).await,
+        Err(PostError::Shutdown)
+    ));
+    // queued events are still delivered
+    assert!(!shutdown.is_finished());
+    gate.add_permits(2);
+    shutdown.await.unwrap().unwrap();
+    assert_eq!(handled.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_shutdown_cancels_context() {
+    let eventbus = Eventbus::new();
+    let cancelled = Arc::new(AtomicUsize::new(0));
+    let listener = Cooperative {
+        listener_id: Default::default(),
+        cancelled: cancelled.clone(),
+    };
+    eventbus
+        .register_context("long", listener, ListenerOptions::new())
+        .await;
+    let topic = eventbus.create_topic("long").await;
+    tokio::spawn(async move { topic.post_message(Message { id: 1 }).await });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    // the handler stops once its context is cancelled, well before the deadline
+    eventbus.shutdown(Duration::from_secs(10)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(cancelled.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_shutdown_timeout() {
+    let eventbus = Eventbus::new();
+    let topic = TopicKey::from("stuck");
+    let listener = Gated {
+        gate: Arc::new(tokio::sync::Semaphore::new(0)),
+        handled: Default::default(),
+    };
+    eventbus
+        .register::<Job, _, _>(topic.clone(), listener)
+        .await;
+    let topic = eventbus.create_topic(topic).await;
+    tokio::spawn(async move { topic.post_message(Job).await });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let result = eventbus.shutdown(Duration::from_millis(10)).await;
+    assert!(matches!(result, Err(ShutdownError::Timeout(1))));
+}
+
+#[derive(Default)]
+struct Hooked(Arc<std::sync::Mutex<Vec<String>>>);
+
+#[async_trait::async_trait]
+impl Listener<Job> for Hooked {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        Ok(())
+    }
+
+    async fn on_register(&self, topic: &TopicKey, _: u64) {
+        self.0.lock().unwrap().push(format!("register {}", topic));
+    }
+
+    async fn on_unregister(&self, topic: &TopicKey, _: u64) {
+        self.0.lock().unwrap().push(format!("unregister {}", topic));
+    }
+
+    async fn on_bus_shutdown(&self, topic: &TopicKey, _: u64) {
+        self.0.lock().unwrap().push(format!("shutdown {}", topic));
+    }
+}
+
+#[tokio::test]
+async fn test_lifecycle_hooks() {
+    let eventbus = Eventbus::new();
+    let first = Hooked::default();
+    let second = Hooked::default();
+    let (first_calls, second_calls) = (first.0.clone(), second.0.clone());
+    let listener = eventbus.register(TopicKey::from("hooked"), first).await;
+    eventbus
+        .register_with(
+            TopicKey::from("hooked"),
+            second,
+            ListenerOptions::new().group("workers"),
+        )
+        .await;
+    listener.unregister().await;
+    eventbus.shutdown(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(
+        *first_calls.lock().unwrap(),
+        vec!["register hooked", "unregister hooked"]
+    );
+    assert_eq!(
+        *second_calls.lock().unwrap(),
+        vec!["register hooked", "shutdown hooked"]
+    );
+}
+
+struct Named;
+
+#[async_trait::async_trait]
+impl Listener<Job> for Named {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        Ok(())
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some("named")
+    }
+}
+
+#[tokio::test]
+async fn test_topics() {
+    let eventbus = Eventbus::new();
+    let named = eventbus.register(TopicKey::from("jobs"), Named).await;
+    let member = eventbus
+        .register_with(
+            TopicKey::from("jobs"),
+            Named,
+            ListenerOptions::new()
+                .group("workers")
+                .timeout(Duration::from_secs(1)),
+        )
+        .await;
+    eventbus.create_topic::<u64, _>("numbers").await;
+
+    let topics = eventbus.topics().await;
+    assert_eq!(topics.len(), 2);
+    assert_eq!(topics[0].key, TopicKey::from("jobs"));
+    assert!(topics[0].type_name.ends_with("Job"));
+    assert_eq!(topics[0].listener_count(), 2);
+    assert_eq!(topics[0].listeners[0].id, named.id());
+    assert_eq!(topics[0].listeners[0].group, None);
+    assert_eq!(topics[0].listeners[1].id, member.id());
+    assert_eq!(topics[0].listeners[1].group.as_deref(), Some("workers"));
+    assert_eq!(topics[0].listeners[1].name.as_deref(), Some("named"));
+    assert_eq!(topics[1].key, TopicKey::from("numbers"));
+    assert_eq!(topics[1].type_name, "u64");
+    assert_eq!(topics[1].listener_count(), 0);
+}
+
+#[tokio::test]
+async fn test_prune_topics() {
+    let eventbus = Eventbus::new();
+    // posting to a topic without listeners leaves nothing behind
+    eventbus
+        .post(&Event::new(TopicKey::from("nobody"), Job))
+        .await
+        .unwrap();
+    assert!(eventbus.topics().await.is_empty());
+
+    let listener = eventbus.register(TopicKey::from("jobs"), Named).await;
+    let topic = eventbus.create_topic::<Job, _>("jobs").await;
+    listener.unregister().await;
+    // the `Topic` handle keeps the topic alive
+    assert_eq!(eventbus.topics().await.len(), 1);
+    assert_eq!(eventbus.prune_topics().await, 0);
+    drop(topic);
+    assert_eq!(eventbus.prune_topics().await, 1);
+
+    let listener = eventbus.register(TopicKey::from("jobs"), Named).await;
+    listener.unregister().await;
+    assert!(eventbus.topics().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_type_check() {
+    let eventbus = Eventbus::with_type_check(TypeCheck::Deny);
+    eventbus.register(TopicKey::from("jobs"), Named).await;
+    eventbus
+        .post(&Event::new(TopicKey::from("jobs"), Job))
+        .await
+        .unwrap();
+    assert!(matches!(
+        eventbus
+            .post(&Event::new(TopicKey::from("jobs"), 42u64))
+            .await,
+        Err(PostError::TypeMismatch { found: "u64", .. })
+    ));
+    let conflicts = eventbus.type_conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].key, TopicKey::from("jobs"));
+    assert!(conflicts[0].type_names[0].ends_with("Job"));
+    assert_eq!(conflicts[0].type_names[1], "u64");
+}
+
+#[tokio::test]
+async fn test_type_check_registration() {
+    let eventbus = Eventbus::with_type_check(TypeCheck::Deny);
+    eventbus.register(TopicKey::from("jobs"), Named).await;
+    assert!(matches!(
+        eventbus.try_register(TopicKey::from("jobs"), Handler).await,
+        Err(PostError::TypeMismatch { found, .. }) if found.ends_with("Message")
+    ));
+    assert!(eventbus
+        .try_create_topic::<Message, _>("jobs")
+        .await
+        .is_err());
+    assert!(eventbus.try_create_topic::<Job, _>("jobs").await.is_ok());
 
-    handler_b.unregister().await;
-    let topic_a = bridged_a.create_topic(topic.clone()).await;
-    let event = Event::new(topic_a.get_key().clone(), Message { id: 1 });
-    topic_a.post(&event).await.unwrap();
+    // mismatched registrations and configurations are skipped without panicking
+    let counter = Arc::new(AtomicUsize::new(0));
+    eventbus
+        .register(TopicKey::from("jobs"), Counter(counter.clone()))
+        .await;
+    eventbus
+        .enable_queue::<u64, _>(TopicKey::from("jobs"), 4, OverflowPolicy::Wait)
+        .await;
+    eventbus
+        .set_partition_key("jobs", |message: &Message| message.id)
+        .await;
+    let topics = eventbus.topics().await;
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].listeners.len(), 1);
+
+    // posts through a mismatched topic are rejected
+    let topic = eventbus.create_topic::<Message, _>("jobs").await;
+    assert!(matches!(
+        topic.post_message(Message { id: 1 }).await,
+        Err(PostError::TypeMismatch { .. })
+    ));
+    assert_eq!(counter.load(Ordering::SeqCst), 0);
+
+    // so are events forwarded by a static eventbus
+    let mut pipeline = Pipeline::new();
+    eventbus.register(TopicKey::from("counted"), Named).await;
+    pipeline.forward_to(&eventbus);
+    assert!(matches!(
+        pipeline.counted.post_message(Message { id: 1 }).await,
+        Err(PostError::TypeMismatch { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_type_check_release() {
+    let eventbus = Eventbus::with_type_check(TypeCheck::Deny);
+    let listener = eventbus.register(TopicKey::from("jobs"), Named).await;
+    assert!(eventbus
+        .post(&Event::new(TopicKey::from("jobs"), 42u64))
+        .await
+        .is_err());
+    eventbus.unregister(listener).await;
+    // the rejected type is not bound once the bound one is released
+    eventbus.register(TopicKey::from("jobs"), Handler).await;
+    assert!(matches!(
+        eventbus
+            .post(&Event::new(TopicKey::from("jobs"), 42u64))
+            .await,
+        Err(PostError::TypeMismatch { found: "u64", .. })
+    ));
+    assert!(eventbus.type_conflicts()[0].type_names[0].ends_with("Message"));
+}
+
+#[tokio::test]
+async fn test_type_check_unbound_post() {
+    let eventbus = Eventbus::with_type_check(TypeCheck::Warn);
+    // posts to topics without listeners do not bind them
+    eventbus
+        .post(&Event::new(TopicKey::from("jobs"), Job))
+        .await
+        .unwrap();
+    eventbus
+        .post(&Event::new(TopicKey::from("jobs"), 42u64))
+        .await
+        .unwrap();
+    assert!(eventbus.type_conflicts().is_empty());
+}
+
+#[derive(Default)]
+struct Audit(Arc<std::sync::Mutex<Vec<&'static str>>>);
+
+#[async_trait::async_trait]
+impl AnyListener for Audit {
+    async fn handle(&self, event: &AnyEvent<'_>) -> Result<(), ListenerError> {
+        if let Some(number) = event.downcast_ref::<u64>() {
+            assert_eq!(*number, 42);
+        }
+        self.0.lock().unwrap().push(event.type_name());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_any_listener() {
+    let eventbus = Eventbus::new();
+    let audit = Audit::default();
+    let seen = audit.0.clone();
+    let listener = eventbus.register_any("audited", audit).await;
+    eventbus.register(TopicKey::from("audited"), Named).await;
+    let event = Event::new(TopicKey::from("audited"), Job);
+    eventbus.post(&event).await.unwrap();
+    let event = Event::new(TopicKey::from("audited"), 42u64);
+    eventbus.post(&event).await.unwrap();
+    listener.unregister().await;
+    eventbus.post(&event).await.unwrap();
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 2);
+    assert!(seen[0].ends_with("Job"));
+    assert_eq!(seen[1], "u64");
+}
+
+#[derive(Default)]
+struct Tapped(Arc<std::sync::Mutex<Vec<String>>>);
+
+impl Wiretap for Tapped {
+    fn on_post(&self, record: &PostRecord<'_>) {
+        let outcome = match record.outcome {
+            PostOutcome::Delivered { failures } => format!("delivered, {} failed", failures),
+            PostOutcome::Queued => "queued".to_string(),
+            PostOutcome::Rejected(e) => e.to_string(),
+        };
+        self.0.lock().unwrap().push(format!(
+            "[{}] {:?} to {} listeners: {}",
+            record.topic, record.message, record.listeners, outcome
+        ));
+    }
+}
+
+struct Failing;
+
+#[async_trait::async_trait]
+impl Listener<Job> for Failing {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        Err(PostError::Shutdown.into())
+    }
+}
+
+#[tokio::test]
+async fn test_wiretap() {
+    let eventbus = Eventbus::new();
+    let tapped = Tapped::default();
+    let records = tapped.0.clone();
+    eventbus.set_wiretap(tapped);
+    eventbus.wiretap_debug::<u64>();
+    eventbus.register(TopicKey::from("jobs"), Named).await;
+    eventbus.register(TopicKey::from("jobs"), Failing).await;
+    eventbus
+        .post(&Event::new(TopicKey::from("jobs"), Job))
+        .await
+        .unwrap();
+    eventbus
+        .post(&Event::new(TopicKey::from("numbers"), 42u64))
+        .await
+        .unwrap();
+    eventbus.clear_wiretap();
+    eventbus
+        .post(&Event::new(TopicKey::from("numbers"), 42u64))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *records.lock().unwrap(),
+        vec![
+            "[jobs] None to 2 listeners: delivered, 1 failed",
+            "[numbers] Some(\"42\") to 0 listeners: delivered, 0 failed",
+        ]
+    );
+}
+
+struct Panicking;
+
+#[async_trait::async_trait]
+impl Listener<Job> for Panicking {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        panic!("boom")
+    }
+}
+
+struct Busy;
+
+#[async_trait::async_trait]
+impl Listener<Job> for Busy {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        Err(ListenerError::retryable("busy"))
+    }
+}
+
+struct Rejecting;
+
+#[async_trait::async_trait]
+impl Listener<Job> for Rejecting {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        Err(ListenerError::rejected("filtered"))
+    }
+}
+
+struct JobCounter(Arc<AtomicUsize>);
+
+#[async_trait::async_trait]
+impl Listener<Job> for JobCounter {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_listener_errors() {
+    let eventbus = Eventbus::new();
+    let tapped = Tapped::default();
+    let records = tapped.0.clone();
+    eventbus.set_wiretap(tapped);
+    eventbus.register(TopicKey::from("jobs"), Panicking).await;
+    // members fail over to the next one on retryable errors
+    eventbus
+        .register_group(TopicKey::from("jobs"), "workers", Busy)
+        .await;
+    eventbus
+        .register_group(TopicKey::from("jobs"), "workers", Busy)
+        .await;
+    eventbus
+        .register_group(TopicKey::from("jobs"), "workers", Named)
+        .await;
+    eventbus
+        .post(&Event::new(TopicKey::from("jobs"), Job))
+        .await
+        .unwrap();
+    // only the panicking broadcast listener fails, the group handled the event
+    assert_eq!(
+        *records.lock().unwrap(),
+        vec!["[jobs] None to 4 listeners: delivered, 1 failed"]
+    );
+
+    assert!(matches!(
+        listener_panicked(Box::new("boom")),
+        ListenerError::Panicked(message) if message == "boom"
+    ));
+    assert!(ListenerError::Timeout(Duration::from_secs(1)).is_retryable());
+    assert!(ListenerError::retryable("busy").is_retryable());
+    assert!(!ListenerError::from("invalid").is_retryable());
+    assert!(!ListenerError::rejected("filtered").is_retryable());
+    let parsed: Result<u64, _> = "42".parse::<u64>().fatal();
+    assert_eq!(parsed.unwrap(), 42);
+}
+
+#[tokio::test]
+async fn test_group_fatal_errors() {
+    let eventbus = Eventbus::new();
+    let failures = Arc::new(AtomicUsize::new(0));
+    let counter = failures.clone();
+    eventbus.set_error_handler(move |_: &ListenerFailure<'_>| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    let handled = Arc::new(AtomicUsize::new(0));
+    // rejected, panicking and fatal events are not re-dispatched to the other members
+    eventbus
+        .register_group(TopicKey::from("jobs"), "workers", Rejecting)
+        .await;
+    eventbus
+        .register_group(TopicKey::from("jobs"), "workers", Panicking)
+        .await;
+    eventbus
+        .register_group(TopicKey::from("jobs"), "workers", Failing)
+        .await;
+    eventbus
+        .register_group(
+            TopicKey::from("jobs"),
+            "workers",
+            JobCounter(handled.clone()),
+        )
+        .await;
+    for _ in 0..3 {
+        eventbus
+            .post(&Event::new(TopicKey::from("jobs"), Job))
+            .await
+            .unwrap();
+    }
+    assert_eq!(failures.load(Ordering::SeqCst), 3);
+    assert_eq!(handled.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_error_handler() {
+    let eventbus = Eventbus::new();
+    let global = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let failures = global.clone();
+    eventbus.set_error_handler(move |failure: &ListenerFailure<'_>| {
+        failures.lock().unwrap().push((
+            failure.topic.to_string(),
+            failure.listener_id,
+            failure.group.map(str::to_string),
+        ))
+    });
+    let audited = Arc::new(AtomicUsize::new(0));
+    let counter = audited.clone();
+    eventbus.set_topic_error_handler("audited", move |failure: &ListenerFailure<'_>| {
+        assert!(matches!(failure.error, ListenerError::Panicked(_)));
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+
+    let failing = eventbus.register(TopicKey::from("jobs"), Failing).await;
+    let member = eventbus
+        .register_group(TopicKey::from("jobs"), "workers", Failing)
+        .await;
+    eventbus
+        .register(TopicKey::from("audited"), Panicking)
+        .await;
+    eventbus
+        .post(&Event::new(TopicKey::from("jobs"), Job))
+        .await
+        .unwrap();
+    eventbus
+        .post(&Event::new(TopicKey::from("audited"), Job))
+        .await
+        .unwrap();
+
+    let mut failures = global.lock().unwrap().clone();
+    failures.sort();
+    let mut expected = vec![
+        ("jobs".to_string(), failing.id(), None),
+        ("jobs".to_string(), member.id(), Some("workers".to_string())),
+    ];
+    expected.sort();
+    assert_eq!(failures, expected);
+    // the topic handler takes precedence over the global one
+    assert_eq!(audited.load(Ordering::SeqCst), 1);
+}
+
+struct BlockingCounter(Arc<AtomicUsize>);
+
+impl SyncListener<Message> for BlockingCounter {
+    fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sync_listeners() {
+    let eventbus = Eventbus::new();
+    let counter = Arc::new(AtomicUsize::new(0));
+    let blocking =
+        eventbus.register_blocking(TopicKey::from("foobar"), BlockingCounter(counter.clone()));
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        eventbus
+            .register(TopicKey::from("foobar"), Counter(counter.clone()))
+            .await;
+        eventbus
+            .post(&Event::new(TopicKey::from("foobar"), Message { id: 1 }))
+            .await
+            .unwrap();
+    });
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+    // both kinds of listeners are reached from non-async code as well
+    eventbus
+        .post_blocking(&Event::new(TopicKey::from("foobar"), Message { id: 2 }))
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+    blocking.unregister_blocking();
+    eventbus
+        .post_blocking(&Event::new(TopicKey::from("foobar"), Message { id: 3 }))
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn test_listener_snapshot() {
+    let eventbus = Eventbus::new();
+    let broadcast = Arc::new(AtomicUsize::new(0));
+    let member = Arc::new(AtomicUsize::new(0));
+    eventbus
+        .register(TopicKey::from("foobar"), Counter(broadcast.clone()))
+        .await;
+    let topic = eventbus.create_topic::<Message, _>("foobar").await;
+    assert_eq!(topic.get_listeners().snapshot().unwrap().len(), 1);
+
+    // a consumer group needs the lock to pick its member
+    let grouped = eventbus
+        .register_group(TopicKey::from("foobar"), "workers", Counter(member.clone()))
+        .await;
+    assert!(topic.get_listeners().snapshot().is_none());
+    topic.post_message(Message { id: 1 }).await.unwrap();
+    assert_eq!(broadcast.load(Ordering::SeqCst), 1);
+    assert_eq!(member.load(Ordering::SeqCst), 1);
+
+    grouped.unregister().await;
+    assert_eq!(topic.get_listeners().snapshot().unwrap().len(), 1);
+    topic.post_message(Message { id: 2 }).await.unwrap();
+    assert_eq!(broadcast.load(Ordering::SeqCst), 2);
+    assert_eq!(member.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_posts() {
+    let eventbus = Eventbus::new();
+    let counter = Arc::new(AtomicUsize::new(0));
+    for topic in 0..8 {
+        eventbus
+            .register(
+                TopicKey::from(topic.to_string().into_bytes()),
+                Counter(counter.clone()),
+            )
+            .await;
+    }
+    let posters: Vec<_> = (0..8)
+        .map(|topic| {
+            let eventbus = eventbus.clone();
+            tokio::spawn(async move {
+                for id in 0..100 {
+                    eventbus
+                        .post(&Event::new(
+                            TopicKey::from(topic.to_string().into_bytes()),
+                            Message { id },
+                        ))
+                        .await
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    // listeners come and go while posting
+    for _ in 0..50 {
+        let listener = eventbus
+            .register(TopicKey::from("0"), Counter(Arc::new(AtomicUsize::new(0))))
+            .await;
+        listener.unregister().await;
+    }
+    for poster in posters {
+        poster.await.unwrap();
+    }
+    assert_eq!(counter.load(Ordering::SeqCst), 800);
+}
+
+#[tokio::test]
+async fn test_topic_handle() {
+    let eventbus = Eventbus::new();
+    let topic = eventbus
+        .create_topic::<Message, _>(b"foobar".to_vec())
+        .await;
+    // handles of the same topic share its listeners, whatever the key was built from
+    assert!(Arc::ptr_eq(
+        &topic.event_listeners,
+        &eventbus
+            .create_topic::<Message, _>("foobar")
+            .await
+            .event_listeners
+    ));
+    assert!(!Arc::ptr_eq(
+        &topic.event_listeners,
+        &eventbus
+            .create_topic::<Message, _>("other")
+            .await
+            .event_listeners
+    ));
+
+    // listeners registered after the handle is created are reached through it
+    let counter = Arc::new(AtomicUsize::new(0));
+    eventbus
+        .register(TopicKey::from("foobar"), Counter(counter.clone()))
+        .await;
+    let audit = Arc::new(std::sync::Mutex::new(Vec::new()));
+    eventbus
+        .register_any(TopicKey::from("foobar"), Audit(audit.clone()))
+        .await;
+    topic.post_message(Message { id: 1 }).await.unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+    assert_eq!(audit.lock().unwrap().len(), 1);
+
+    // events of other topics are posted to the eventbus
+    eventbus
+        .register(TopicKey::from("other"), Counter(counter.clone()))
+        .await;
+    topic
+        .post(&Event::new(TopicKey::from("other"), Message { id: 2 }))
+        .await
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+    assert_eq!(audit.lock().unwrap().len(), 1);
+}
+
+static_bus! {
+    struct Pipeline {
+        counted: Message => Counter,
+        jobs: Job,
+    }
+}
+
+#[tokio::test]
+async fn test_static_bus() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let mut pipeline = Pipeline::new();
+    pipeline.counted.register(Counter(counter.clone()));
+    pipeline.jobs.register_boxed(Failing);
+    assert_eq!(pipeline.counted.get_key(), &TopicKey::from("counted"));
+
+    pipeline
+        .counted
+        .post_message(Message { id: 1 })
+        .await
+        .unwrap();
+    pipeline.jobs.post_message(Job).await.unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+    // events are forwarded after the listeners of the static eventbus
+    let eventbus = Eventbus::new();
+    eventbus
+        .register(TopicKey::from("counted"), Counter(counter.clone()))
+        .await;
+    pipeline.forward_to(&eventbus);
+    pipeline
+        .counted
+        .post_message(Message { id: 2 })
+        .await
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_static_bus_failures() {
+    let mut pipeline = Pipeline::new();
+    pipeline.jobs.register_boxed(Named);
+    pipeline.jobs.register_boxed(Panicking);
+    pipeline.jobs.register_boxed(Failing);
+
+    // failures go to the handlers of the forwarded eventbus, panics included
+    let eventbus = Eventbus::new();
+    let forwarded = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let failures = forwarded.clone();
+    eventbus.set_error_handler(move |failure: &ListenerFailure<'_>| {
+        failures.lock().unwrap().push((
+            failure.topic.to_string(),
+            failure.listener_id,
+            matches!(failure.error, ListenerError::Panicked(_)),
+        ))
+    });
+    pipeline.forward_to(&eventbus);
+    pipeline.jobs.post_message(Job).await.unwrap();
+    assert_eq!(
+        *forwarded.lock().unwrap(),
+        vec![
+            ("jobs".to_string(), 1, true),
+            ("jobs".to_string(), 2, false)
+        ]
+    );
+
+    // the handler of the topic takes precedence
+    let own = Arc::new(AtomicUsize::new(0));
+    let counter = own.clone();
+    pipeline
+        .jobs
+        .set_error_handler(move |_: &ListenerFailure<'_>| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+    pipeline.jobs.post_message(Job).await.unwrap();
+    assert_eq!(own.load(Ordering::SeqCst), 2);
+    assert_eq!(forwarded.lock().unwrap().len(), 2);
+}
+
+struct NativeCounter(Arc<AtomicUsize>);
+
+impl NativeListener<Message> for NativeCounter {
+    async fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_native_listener() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let mut topic = StaticTopic::<Message, NativeCounter>::new("foobar");
+    topic.register(NativeCounter(counter.clone()));
+    topic.post_message(Message { id: 1 }).await.unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+    // native listeners are boxed by the dynamic eventbus
+    let eventbus = Eventbus::new();
+    eventbus
+        .register_native("foobar", NativeCounter(counter.clone()))
+        .await;
+    eventbus
+        .post(&Event::new(TopicKey::from("foobar"), Message { id: 2 }))
+        .await
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+}
+
+/// blocks its worker thread until every listener of the event is running
+struct Rendezvous(Arc<std::sync::Barrier>);
+
+#[async_trait::async_trait]
+impl Listener<Job> for Rendezvous {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        self.0.wait();
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_parallel_delivery() {
+    let eventbus = Eventbus::new();
+    eventbus.enable_parallel::<Job, _>("foobar", None).await;
+    let barrier = Arc::new(std::sync::Barrier::new(2));
+    eventbus
+        .register("foobar", Rendezvous(barrier.clone()))
+        .await;
+    eventbus.register("foobar", Rendezvous(barrier)).await;
+    eventbus.register("foobar", Failing).await;
+    let topic = eventbus.create_topic("foobar").await;
+    let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+    eventbus.set_wiretap(Tapped(records.clone()));
+    // both listeners block until the other one runs, so they run on separate threads
+    topic.post_message(Job).await.unwrap();
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 1);
+    assert!(records[0].ends_with("to 3 listeners: delivered, 1 failed"));
+}
+
+struct Overlap {
+    running: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl Listener<Job> for Overlap {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(running, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_parallel_max_tasks() {
+    let eventbus = Eventbus::new();
+    eventbus.enable_parallel::<Job, _>("foobar", Some(2)).await;
+    let running = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    for _ in 0..4 {
+        eventbus
+            .register(
+                "foobar",
+                Overlap {
+                    running: running.clone(),
+                    peak: peak.clone(),
+                },
+            )
+            .await;
+    }
+    eventbus
+        .post(&Event::new(TopicKey::from("foobar"), Job))
+        .await
+        .unwrap();
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+    assert_eq!(running.load(Ordering::SeqCst), 0);
+}
+
+/// posts to `topic` while `remaining` is not exhausted
+struct Echo {
+    eventbus: Eventbus,
+    topic: &'static str,
+    remaining: Arc<AtomicUsize>,
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl Listener<Job> for Echo {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let remaining = self
+            .remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+        if remaining.is_ok() {
+            self.eventbus
+                .post(&Event::new(TopicKey::from(self.topic), Job))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_parallel_reentrant_post() {
+    let eventbus = Eventbus::new();
+    eventbus.enable_parallel::<Job, _>("ping", Some(1)).await;
+    eventbus.enable_parallel::<Job, _>("pong", Some(1)).await;
+    let remaining = Arc::new(AtomicUsize::new(3));
+    let calls = Arc::new(AtomicUsize::new(0));
+    for (from, to) in [("ping", "ping"), ("ping", "pong"), ("pong", "ping")] {
+        let echo = Echo {
+            eventbus: eventbus.clone(),
+            topic: to,
+            remaining: remaining.clone(),
+            calls: calls.clone(),
+        };
+        eventbus.register(from, echo).await;
+    }
+    // every listener holds the single permit of its topic while it posts back to it
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        eventbus.post(&Event::new(TopicKey::from("ping"), Job)),
+    )
+    .await
+    .expect("reentrant posts must not wait for the permits of their own tasks")
+    .unwrap();
+    assert_eq!(remaining.load(Ordering::SeqCst), 0);
+    assert!(calls.load(Ordering::SeqCst) > 3);
 }
@@ -0,0 +1,100 @@
+use crate::bridge::{BridgePostError, EventbusBridge};
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+struct HandlerA;
+struct HandlerB;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Message {
+    id: u8,
+}
+
+#[async_trait::async_trait]
+impl Listener<Message> for HandlerA {
+    async fn handle(&self, event: &Event<Message>) -> Result<(), ListenerError> {
+        println!("A: {:?}", event);
+        Ok(())
+    }
+}
+#[async_trait::async_trait]
+impl Listener<Message> for HandlerB {
+    async fn handle(&self, event: &Event<Message>) -> Result<(), ListenerError> {
+        println!("B: {:?}", event);
+        Ok(())
+    }
+}
+
+struct Counter(Arc<AtomicUsize>);
+
+#[async_trait::async_trait]
+impl Listener<Message> for Counter {
+    async fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_bridge() {
+    let eventbus_a = Eventbus::new();
+    let eventbus_b = Eventbus::new();
+    let bridged_a = EventbusBridge::new(eventbus_a);
+    let bridged_b = EventbusBridge::new(eventbus_b);
+
+    let server_a = bridged_a.clone().listen("127.0.0.1:50001".parse().unwrap());
+    let server_a = tokio::spawn(server_a);
+    let server_b = bridged_b.clone().listen("127.0.0.1:50002".parse().unwrap());
+    tokio::spawn(server_b);
+
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    bridged_a.connect("http://127.0.0.1:50002").await.unwrap();
+    bridged_b.connect("http://127.0.0.1:50001").await.unwrap();
+
+    let topic = TopicKey::from("foobar");
+
+    let handler_a = bridged_a.register(topic.clone(), HandlerA).await;
+    let handler_b = bridged_b.register(topic.clone(), HandlerB).await;
+
+    let topic_a = bridged_a.create_topic(topic.clone()).await;
+    let event = Event::new(topic_a.get_key().clone(), Message { id: 1 });
+    topic_a.post(&event).await.unwrap();
+
+    let topic_b = bridged_b.create_topic(topic.clone()).await;
+    let event = Event::new(topic_b.get_key().clone(), Message { id: 2 });
+    topic_b.post(&event).await.unwrap();
+
+    handler_a.unregister().await;
+    let topic_b = bridged_b.create_topic(topic.clone()).await;
+    let event = Event::new(topic_b.get_key().clone(), Message { id: 2 });
+    topic_b.post(&event).await.unwrap();
+
+    handler_b.unregister().await;
+    let topic_a = bridged_a.create_topic(topic.clone()).await;
+    let event = Event::new(topic_a.get_key().clone(), Message { id: 1 });
+    topic_a.post(&event).await.unwrap();
+
+    // the server stops along with the eventbus
+    let counter = Arc::new(AtomicUsize::new(0));
+    bridged_b
+        .register(topic.clone(), Counter(counter.clone()))
+        .await;
+    bridged_a
+        .get_bus()
+        .shutdown(Duration::from_secs(1))
+        .await
+        .unwrap();
+    server_a.await.unwrap().unwrap();
+
+    // events rejected by the local eventbus are not forwarded
+    let event = Event::new(topic_a.get_key().clone(), Message { id: 3 });
+    assert!(matches!(
+        topic_a.post(&event).await,
+        Err(BridgePostError::Rejected(PostError::Shutdown))
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::SeqCst), 0);
+}
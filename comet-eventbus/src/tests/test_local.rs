@@ -0,0 +1,47 @@
+use crate::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// `!Send` message
+#[derive(Debug)]
+struct Clicked(Rc<str>);
+
+struct History(Rc<RefCell<Vec<String>>>);
+
+#[async_trait(?Send)]
+impl LocalListener<Clicked> for History {
+    async fn handle(&self, event: &Event<Clicked>) -> Result<(), ListenerError> {
+        let history = self.0.clone();
+        // `Rc` held across an await point
+        futures::future::ready(()).await;
+        history.borrow_mut().push(event.0.to_string());
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_local_eventbus() {
+    let local = tokio::task::LocalSet::new();
+    local
+        .run_until(async {
+            let eventbus = LocalEventbus::new();
+            let history = Rc::new(RefCell::new(Vec::new()));
+            let listener = eventbus.register("button", History(history.clone())).await;
+            let topic = eventbus.create_topic("button").await;
+            // posts can be spawned on the local set
+            let post = tokio::task::spawn_local({
+                let eventbus = eventbus.clone();
+                async move {
+                    eventbus
+                        .post(&Event::new("button", Clicked(Rc::from("ok"))))
+                        .await
+                }
+            });
+            post.await.unwrap();
+            topic.post_message(Clicked(Rc::from("cancel"))).await;
+            listener.unregister().await;
+            topic.post_message(Clicked(Rc::from("ignored"))).await;
+            assert_eq!(*history.borrow(), vec!["ok", "cancel"]);
+        })
+        .await;
+}
@@ -0,0 +1,87 @@
+use crate::instrument::*;
+use crate::*;
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use metrics_util::MetricKind;
+
+struct Handler;
+
+#[async_trait::async_trait]
+impl Listener<u64> for Handler {
+    async fn handle(&self, event: &Event<u64>) -> Result<(), ListenerError> {
+        match **event {
+            0 => Err(PostError::Shutdown.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_metrics() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            label_topics(["numbers"]);
+            let eventbus = Eventbus::new();
+            let listener = eventbus.register("numbers", Handler).await;
+            eventbus.register("unlisted", Handler).await;
+            eventbus
+                .post(&Event::new(TopicKey::from("unlisted"), 1u64))
+                .await
+                .unwrap();
+            let topic = eventbus.create_topic("numbers").await;
+            topic.post_message(0u64).await.unwrap();
+            topic.post_message(1u64).await.unwrap();
+            topic.post_message(2u64).await.unwrap();
+            listener.unregister().await;
+        });
+    });
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let labelled = |kind: MetricKind, name: &str, topic: &str| {
+        snapshot
+            .iter()
+            .find(|(key, ..)| {
+                key.kind() == kind
+                    && key.key().name() == name
+                    && key
+                        .key()
+                        .labels()
+                        .any(|label| label.key() == "topic" && label.value() == topic)
+            })
+            .map(|(.., value)| value)
+    };
+    let value = |kind: MetricKind, name: &str| labelled(kind, name, "numbers");
+    assert_eq!(
+        value(MetricKind::Counter, POSTS),
+        Some(&DebugValue::Counter(3))
+    );
+    assert_eq!(
+        value(MetricKind::Counter, DELIVERIES),
+        Some(&DebugValue::Counter(2))
+    );
+    assert_eq!(
+        value(MetricKind::Counter, FAILURES),
+        Some(&DebugValue::Counter(1))
+    );
+    assert!(matches!(
+        value(MetricKind::Histogram, LISTENER_DURATION),
+        Some(DebugValue::Histogram(durations)) if durations.len() == 3
+    ));
+    assert_eq!(
+        value(MetricKind::Gauge, LISTENERS),
+        Some(&DebugValue::Gauge(0.0.into()))
+    );
+    // topics which are not labelled share a single series
+    assert_eq!(
+        labelled(MetricKind::Counter, POSTS, OTHER_TOPICS),
+        Some(&DebugValue::Counter(1))
+    );
+    assert_eq!(
+        labelled(MetricKind::Gauge, LISTENERS, OTHER_TOPICS),
+        Some(&DebugValue::Gauge(1.0.into()))
+    );
+}
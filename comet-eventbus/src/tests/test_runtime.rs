@@ -0,0 +1,152 @@
+use crate::*;
+use futures::future::BoxFuture;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Clone)]
+struct Job;
+
+struct Counter(Arc<AtomicUsize>);
+
+#[async_trait]
+impl Listener<Job> for Counter {
+    async fn handle(&self, _: &Event<Job>) -> Result<(), ListenerError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+struct Stuck;
+
+#[async_trait]
+impl ContextListener<Job> for Stuck {
+    async fn handle(&self, _: &Event<Job>, cx: &ListenerContext) -> Result<(), ListenerError> {
+        cx.cancelled().await;
+        Ok(())
+    }
+}
+
+struct Smol;
+
+impl Runtime for Smol {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        smol::spawn(future).detach();
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(async move {
+            smol::Timer::after(duration).await;
+        })
+    }
+}
+
+/// queues, timeouts and shutdown, which need the runtime of the eventbus
+async fn exercise(eventbus: Eventbus) {
+    let handled = Arc::new(AtomicUsize::new(0));
+    let timeouts = Arc::new(AtomicUsize::new(0));
+    let counter = timeouts.clone();
+    eventbus.set_error_handler(move |failure: &ListenerFailure<'_>| {
+        assert!(matches!(failure.error, ListenerError::Timeout(_)));
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    eventbus
+        .register(TopicKey::from("queued"), Counter(handled.clone()))
+        .await;
+    eventbus
+        .enable_queue::<Job, _>(TopicKey::from("queued"), 4, OverflowPolicy::Wait)
+        .await;
+    eventbus
+        .register_context(
+            TopicKey::from("stuck"),
+            Stuck,
+            ListenerOptions::new().timeout(Duration::from_millis(10)),
+        )
+        .await;
+
+    for _ in 0..3 {
+        eventbus
+            .post(&Event::new(TopicKey::from("queued"), Job))
+            .await
+            .unwrap();
+    }
+    eventbus
+        .post(&Event::new(TopicKey::from("stuck"), Job))
+        .await
+        .unwrap();
+    assert_eq!(timeouts.load(Ordering::SeqCst), 1);
+
+    // queued events are drained by the task spawned on the runtime
+    eventbus.shutdown(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(handled.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_smol_runtime() {
+    let eventbus = Eventbus::new();
+    eventbus.set_runtime(Smol);
+    smol::block_on(exercise(eventbus));
+}
+
+#[test]
+fn test_thread_runtime() {
+    let eventbus = Eventbus::new();
+    eventbus.set_runtime(ThreadRuntime);
+    futures::executor::block_on(exercise(eventbus));
+}
+
+/// counts the tasks and timers of the eventbus, run by a `ThreadRuntime`
+#[derive(Default, Clone)]
+struct Counted(Arc<AtomicUsize>);
+
+impl Runtime for Counted {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        ThreadRuntime.spawn(future)
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        ThreadRuntime.sleep(duration)
+    }
+}
+
+#[test]
+fn test_runtime_set_after_registration() {
+    let eventbus = Eventbus::new();
+    eventbus.set_runtime(ThreadRuntime);
+    let handled = Arc::new(AtomicUsize::new(0));
+    futures::executor::block_on(async {
+        eventbus
+            .register(TopicKey::from("queued"), Counter(handled.clone()))
+            .await;
+        eventbus
+            .enable_queue::<Job, _>(TopicKey::from("queued"), 4, OverflowPolicy::Wait)
+            .await;
+        eventbus
+            .register_context(
+                TopicKey::from("stuck"),
+                Stuck,
+                ListenerOptions::new().timeout(Duration::from_millis(10)),
+            )
+            .await;
+    });
+
+    // the mailbox and the listener use the runtime set afterwards
+    let counted = Counted::default();
+    eventbus.set_runtime(counted.clone());
+    futures::executor::block_on(async {
+        eventbus
+            .post(&Event::new(TopicKey::from("queued"), Job))
+            .await
+            .unwrap();
+        eventbus
+            .post(&Event::new(TopicKey::from("stuck"), Job))
+            .await
+            .unwrap();
+        eventbus.shutdown(Duration::from_secs(1)).await.unwrap();
+    });
+    assert_eq!(handled.load(Ordering::SeqCst), 1);
+    // the task draining the mailbox, the timer of the listener and the one of the shutdown
+    assert_eq!(counted.0.load(Ordering::SeqCst), 3);
+}
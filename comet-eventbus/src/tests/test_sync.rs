@@ -1,28 +1,286 @@
 use crate::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
 
-struct Handler;
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Message {
     id: u8,
 }
 
-impl Listener<Message> for Handler {
-    fn handle(&self, event: &Event<Message>) {
-        println!("{:?}", event)
+struct Counter(Arc<AtomicUsize>);
+
+impl SyncListener<Message> for Counter {
+    fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+struct AsyncCounter(Arc<AtomicUsize>);
+
+#[async_trait]
+impl Listener<Message> for AsyncCounter {
+    async fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
     }
 }
 
 #[test]
 fn test() {
     let eventbus = Eventbus::new();
-    let topic = TopicKey::from("foobar");
-    let handler = eventbus.register(topic.clone(), Handler);
-    let topic = eventbus.create_topic(topic.clone());
-    let event = Event::new(topic.key.clone(), Message { id: 1 });
-    topic.post(&event);
-    handler.unregister();
-    let event = Event::new(topic.key.clone(), Message { id: 2 });
-    // this should not produce any output since we already unregister listener
-    topic.post(&event);
+    let counter = Arc::new(AtomicUsize::new(0));
+    let handler = eventbus.register_blocking(TopicKey::from("foobar"), Counter(counter.clone()));
+    // blocking posts reach async listeners as well
+    futures::executor::block_on(
+        eventbus.register(TopicKey::from("foobar"), AsyncCounter(counter.clone())),
+    );
+    eventbus
+        .post_blocking(&Event::new(TopicKey::from("foobar"), Message { id: 1 }))
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+    handler.unregister_blocking();
+    eventbus
+        .post_blocking(&Event::new(TopicKey::from("foobar"), Message { id: 2 }))
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[derive(Default)]
+struct Hooked(Arc<Mutex<Vec<String>>>);
+
+impl SyncListener<Message> for Hooked {
+    fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        Ok(())
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some("hooked")
+    }
+
+    fn on_register(&self, topic: &TopicKey, _: u64) {
+        self.0.lock().unwrap().push(format!("register {}", topic));
+    }
+
+    fn on_unregister(&self, topic: &TopicKey, _: u64) {
+        self.0.lock().unwrap().push(format!("unregister {}", topic));
+    }
+
+    fn on_bus_shutdown(&self, topic: &TopicKey, _: u64) {
+        self.0.lock().unwrap().push(format!("shutdown {}", topic));
+    }
+}
+
+#[test]
+fn test_lifecycle_hooks() {
+    let eventbus = Eventbus::new();
+    let hooked = Hooked::default();
+    let calls = hooked.0.clone();
+    let listener = eventbus.register_blocking(TopicKey::from("hooked"), hooked);
+    listener.unregister_blocking();
+    let hooked = Hooked(calls.clone());
+    eventbus.register_blocking(TopicKey::from("hooked"), hooked);
+    eventbus.shutdown_blocking(Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![
+            "register hooked",
+            "unregister hooked",
+            "register hooked",
+            "shutdown hooked"
+        ]
+    );
+}
+
+#[test]
+fn test_topics() {
+    let eventbus = Eventbus::new();
+    let hooked = eventbus.register_blocking(TopicKey::from("hooked"), Hooked::default());
+    let member =
+        eventbus.register_group_blocking(TopicKey::from("hooked"), "workers", Hooked::default());
+    eventbus.create_topic_blocking::<u64, _>("numbers");
+
+    let topics = eventbus.topics_blocking();
+    assert_eq!(topics.len(), 2);
+    assert_eq!(topics[0].key, TopicKey::from("hooked"));
+    assert!(topics[0].type_name.ends_with("Message"));
+    assert_eq!(topics[0].listener_count(), 2);
+    assert_eq!(topics[0].listeners[0].id, hooked.id());
+    assert_eq!(topics[0].listeners[0].name.as_deref(), Some("hooked"));
+    assert_eq!(topics[0].listeners[1].id, member.id());
+    assert_eq!(topics[0].listeners[1].group.as_deref(), Some("workers"));
+    assert_eq!(topics[1].key, TopicKey::from("numbers"));
+    assert_eq!(topics[1].listener_count(), 0);
+}
+
+struct Panicking;
+
+impl SyncListener<Message> for Panicking {
+    fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        panic!("boom")
+    }
+}
+
+#[test]
+fn test_listener_panics() {
+    let eventbus = Eventbus::new();
+    let counter = Arc::new(AtomicUsize::new(0));
+    eventbus.register_blocking(TopicKey::from("foobar"), Panicking);
+    eventbus.register_blocking(TopicKey::from("foobar"), Counter(counter.clone()));
+    eventbus
+        .post_blocking(&Event::new(TopicKey::from("foobar"), Message { id: 1 }))
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_type_check() {
+    let eventbus = Eventbus::with_type_check(TypeCheck::Deny);
+    eventbus.register_blocking(TopicKey::from("foobar"), Panicking);
+    assert!(matches!(
+        eventbus.post_blocking(&Event::new(TopicKey::from("foobar"), 42u64)),
+        Err(PostError::TypeMismatch { found: "u64", .. })
+    ));
+}
+
+/// forwards every event to another topic, from its blocking handler
+struct Relay(Eventbus);
+
+impl SyncListener<Message> for Relay {
+    fn handle(&self, event: &Event<Message>) -> Result<(), ListenerError> {
+        self.0
+            .post_blocking(&Event::new(TopicKey::from("relayed"), (**event).clone()))
+            .map_err(ListenerError::fatal)
+    }
+}
+
+#[test]
+fn test_nested_post() {
+    let eventbus = Eventbus::new();
+    let counter = Arc::new(AtomicUsize::new(0));
+    eventbus.register_blocking(TopicKey::from("foobar"), Relay(eventbus.clone()));
+    eventbus.register_blocking(TopicKey::from("relayed"), Counter(counter.clone()));
+    eventbus
+        .post_blocking(&Event::new(TopicKey::from("foobar"), Message { id: 1 }))
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+}
+
+struct Forward(Mutex<mpsc::Sender<u8>>);
+
+impl SyncListener<Message> for Forward {
+    fn handle(&self, event: &Event<Message>) -> Result<(), ListenerError> {
+        self.0
+            .lock()
+            .unwrap()
+            .send(event.id)
+            .map_err(ListenerError::fatal)
+    }
+}
+
+#[test]
+fn test_queue_without_runtime() {
+    let eventbus = Eventbus::new();
+    let (tx, rx) = mpsc::channel();
+    eventbus.register_blocking(TopicKey::from("queued"), Forward(Mutex::new(tx)));
+    eventbus.enable_queue_blocking::<Message, _>(TopicKey::from("queued"), 4, OverflowPolicy::Wait);
+    for id in 0..3 {
+        eventbus
+            .post_blocking(&Event::new(TopicKey::from("queued"), Message { id }))
+            .unwrap();
+    }
+    for id in 0..3 {
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), id);
+    }
+}
+
+#[test]
+fn test_parallel_without_runtime() {
+    let eventbus = Eventbus::new();
+    let counter = Arc::new(AtomicUsize::new(0));
+    eventbus.register_blocking(TopicKey::from("parallel"), Counter(counter.clone()));
+    eventbus.register_blocking(TopicKey::from("parallel"), Counter(counter.clone()));
+    eventbus.enable_parallel_blocking::<Message, _>(TopicKey::from("parallel"), Some(1));
+    let topic = eventbus.create_topic_blocking("parallel");
+    topic.post_message_blocking(Message { id: 1 }).unwrap();
+    // the post returns once every task is done
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+}
+
+/// waits for the other listener of the topic, so it succeeds only if both run in parallel
+#[cfg(feature = "sync_parallel")]
+struct Rendezvous(Arc<(Mutex<usize>, std::sync::Condvar)>);
+
+#[cfg(feature = "sync_parallel")]
+impl SyncListener<Message> for Rendezvous {
+    fn handle(&self, _: &Event<Message>) -> Result<(), ListenerError> {
+        let (arrived, all_arrived) = &*self.0;
+        let mut arrived = arrived.lock().unwrap();
+        *arrived += 1;
+        all_arrived.notify_all();
+        let (arrived, timeout) = all_arrived
+            .wait_timeout_while(arrived, Duration::from_secs(5), |arrived| *arrived < 2)
+            .unwrap();
+        drop(arrived);
+        if timeout.timed_out() {
+            return Err(ListenerError::fatal(
+                "listeners were called one after another",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sync_parallel")]
+#[test]
+fn test_sync_parallel() {
+    let eventbus = Eventbus::new();
+    let failures = Arc::new(AtomicUsize::new(0));
+    let counter = failures.clone();
+    eventbus.set_error_handler(move |_: &ListenerFailure<'_>| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    let rendezvous: Arc<(Mutex<usize>, _)> = Arc::default();
+    eventbus.register_blocking(TopicKey::from("foobar"), Rendezvous(rendezvous.clone()));
+    eventbus.register_blocking(TopicKey::from("foobar"), Rendezvous(rendezvous));
+    // the global pool has a single thread on a single core machine
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    pool.install(|| {
+        eventbus.post_blocking(&Event::new(TopicKey::from("foobar"), Message { id: 1 }))
+    })
+    .unwrap();
+    assert_eq!(failures.load(Ordering::SeqCst), 0);
+}
+
+static_bus! {
+    struct Pipeline {
+        counted: Message,
+    }
+}
+
+#[test]
+fn test_static_bus() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let mut pipeline = Pipeline::new();
+    pipeline.counted.register_sync(Counter(counter.clone()));
+    pipeline
+        .counted
+        .post_message_blocking(Message { id: 1 })
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+    // events are forwarded after the listeners of the static eventbus
+    let eventbus = Eventbus::new();
+    eventbus.register_blocking(TopicKey::from("counted"), Counter(counter.clone()));
+    pipeline.forward_to(&eventbus);
+    pipeline
+        .counted
+        .post_message_blocking(Message { id: 2 })
+        .unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
 }
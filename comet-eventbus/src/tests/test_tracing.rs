@@ -0,0 +1,117 @@
+use crate::*;
+use std::sync::{Arc, Mutex};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// record spans as `name < parent name`
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorder {
+    fn on_new_span(&self, _: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        let parent = span.parent().map(|parent| parent.name()).unwrap_or("-");
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{} < {}", span.name(), parent));
+    }
+}
+
+struct Handler;
+
+#[async_trait::async_trait]
+impl Listener<u64> for Handler {
+    async fn handle(&self, _: &Event<u64>) -> Result<(), ListenerError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_tracing() {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let eventbus = Eventbus::new();
+            eventbus.register("numbers", Handler).await;
+            eventbus
+                .post(&Event::new(TopicKey::from("numbers"), 42u64))
+                .await
+                .unwrap();
+        });
+    });
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        vec!["post < -", "listener < post"]
+    );
+}
+
+#[cfg(feature = "otel")]
+mod bridged {
+    use crate::bridge::EventbusBridge;
+    use crate::*;
+    use opentelemetry::trace::{TraceContextExt, TraceId, TracerProvider};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tracing::Instrument;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// record the trace id of the span the event is delivered in
+    #[derive(Clone, Default)]
+    struct TraceRecorder(Arc<Mutex<Option<TraceId>>>);
+
+    #[async_trait::async_trait]
+    impl Listener<u64> for TraceRecorder {
+        async fn handle(&self, _: &Event<u64>) -> Result<(), ListenerError> {
+            let context = tracing::Span::current().context();
+            *self.0.lock().unwrap() = Some(context.span().span_context().trace_id());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_bridge_trace_context() {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // client and server run on the same thread, to share the subscriber
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let server = EventbusBridge::new(Eventbus::new());
+            let recorder = TraceRecorder::default();
+            server
+                .register(TopicKey::from("traced"), recorder.clone())
+                .await;
+            tokio::spawn(server.listen("127.0.0.1:50011".parse().unwrap()));
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            let client = EventbusBridge::new(Eventbus::new());
+            client.connect("http://127.0.0.1:50011").await.unwrap();
+            let span = tracing::info_span!("client");
+            let trace_id = span.context().span().span_context().trace_id();
+            client
+                .post(&Event::new(TopicKey::from("traced"), 42u64))
+                .instrument(span)
+                .await
+                .unwrap();
+
+            assert_ne!(trace_id, TraceId::INVALID);
+            assert_eq!(*recorder.0.lock().unwrap(), Some(trace_id));
+        });
+    }
+}
@@ -2,6 +2,9 @@ use crate::{Event, EventListeners, Eventbus, TopicKey};
 use std::fmt::{Debug, Formatter};
 
 /// A `Topic` wrapper for a `TopicKey`
+///
+/// It holds the listeners of the topic resolved at creation, posting through it skips
+/// every lookup of the eventbus.
 pub struct Topic<T> {
     pub(crate) key: TopicKey,
     pub(crate) bus: Eventbus,
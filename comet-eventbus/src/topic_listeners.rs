@@ -0,0 +1,323 @@
+use crate::consumer_group::{ConsumerGroup, GroupMember, GroupStrategy};
+use crate::fanout::Fanout;
+use crate::mailbox::{Mailbox, MailboxQueue};
+use crate::partition::{Partitioner, Ticket};
+use crate::registry::TopicNode;
+use crate::{Event, Listener, ListenerInfo, Mutex};
+use arc_swap::ArcSwapAny;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use tokio::sync::MutexGuard;
+
+/// Listeners subscribed to a single topic
+pub struct TopicListeners<T> {
+    listeners: HashMap<u64, Arc<dyn Listener<T>>>,
+    groups: HashMap<String, ConsumerGroup<T>>,
+    partitioner: Option<Partitioner<T>>,
+    mailbox: Option<Mailbox>,
+    fanout: Option<Fanout<T>>,
+}
+
+/// plain listeners of a topic with their ids, shared by the posts reading them
+pub(crate) type Snapshot<T> = Arc<Vec<(u64, Arc<dyn Listener<T>>)>>;
+
+/// Listeners of a topic, along with a lock-free snapshot of them for posting
+///
+/// As long as the topic has only plain listeners, i.e. no consumer group, no partition key,
+/// no mailbox and no parallel delivery, posts read the snapshot and never lock the listeners.
+/// The snapshot is refreshed whenever the listeners are changed.
+pub struct TopicEntry<T> {
+    node: Arc<TopicNode>,
+    listeners: Mutex<TopicListeners<T>>,
+    snapshot: ArcSwapAny<Option<Snapshot<T>>>,
+}
+
+/// Lock guard of the listeners of a topic, refreshes the snapshot once dropped if it changed
+pub struct TopicGuard<'a, T> {
+    guard: MutexGuard<'a, TopicListeners<T>>,
+    snapshot: &'a ArcSwapAny<Option<Snapshot<T>>>,
+    dirty: bool,
+}
+
+/// Listeners which should receive an event
+pub(crate) struct DispatchPlan<T> {
+    /// id and listener, every one of them receives the event
+    pub(crate) listeners: Snapshot<T>,
+    /// group name and its candidates, only the first succeeded candidate handles the event
+    pub(crate) groups: Vec<(String, Vec<GroupMember<T>>)>,
+    /// spawns a task per listener if the topic is delivered in parallel
+    pub(crate) fanout: Option<Fanout<T>>,
+}
+
+impl<T> TopicListeners<T> {
+    /// number of subscribed listeners, including members of consumer groups
+    pub fn len(&self) -> usize {
+        self.listeners.len() + self.groups.values().map(|g| g.len()).sum::<usize>()
+    }
+
+    /// check if there is no listener
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// check if a listener is subscribed
+    pub fn contains(&self, rand_id: u64) -> bool {
+        self.listeners.contains_key(&rand_id) || self.groups.values().any(|g| g.contains(rand_id))
+    }
+
+    /// names of consumer groups on this topic
+    pub fn groups(&self) -> impl Iterator<Item = &str> {
+        self.groups.keys().map(String::as_str)
+    }
+
+    pub(crate) fn insert(&mut self, rand_id: u64, listener: Arc<dyn Listener<T>>) {
+        self.listeners.insert(rand_id, listener);
+    }
+
+    pub(crate) fn insert_group_member(
+        &mut self,
+        group: String,
+        rand_id: u64,
+        listener: Arc<dyn Listener<T>>,
+    ) {
+        self.groups
+            .entry(group)
+            .or_insert_with(|| ConsumerGroup::new(GroupStrategy::default()))
+            .insert(rand_id, listener);
+    }
+
+    pub(crate) fn set_group_strategy(&mut self, group: String, strategy: GroupStrategy<T>) {
+        self.groups
+            .entry(group)
+            .or_insert_with(|| ConsumerGroup::new(GroupStrategy::default()))
+            .set_strategy(strategy);
+    }
+
+    pub(crate) fn remove(&mut self, rand_id: u64) -> Option<Arc<dyn Listener<T>>> {
+        let removed = self.listeners.remove(&rand_id).or_else(|| {
+            self.groups
+                .values_mut()
+                .find_map(|group| group.remove(rand_id))
+        });
+        // groups left without members and configuration are dropped with their last member
+        self.groups
+            .retain(|_, group| group.len() > 0 || !group.has_default_strategy());
+        removed
+    }
+
+    /// check if nothing is subscribed to or configured on the topic
+    pub(crate) fn is_idle(&self) -> bool {
+        self.listeners.is_empty()
+            && self.groups.is_empty()
+            && self.partitioner.is_none()
+            && self.mailbox.is_none()
+            && self.fanout.is_none()
+    }
+
+    /// all listeners with their ids, including members of consumer groups
+    pub(crate) fn all(&self) -> Vec<(u64, Arc<dyn Listener<T>>)> {
+        self.listeners
+            .iter()
+            .map(|(rand_id, listener)| (*rand_id, listener.clone()))
+            .chain(self.groups.values().flat_map(ConsumerGroup::members))
+            .collect()
+    }
+
+    /// describe every listener, members of consumer groups come after plain listeners
+    pub(crate) fn describe(&self) -> Vec<ListenerInfo>
+    where
+        T: 'static,
+    {
+        let mut listeners: Vec<_> = self
+            .listeners
+            .iter()
+            .map(|(rand_id, listener)| ListenerInfo::new(*rand_id, None, listener.as_ref()))
+            .collect();
+        listeners.sort_by_key(|info| info.id);
+        let mut groups: Vec<_> = self.groups.iter().collect();
+        groups.sort_by_key(|(group, _)| group.as_str());
+        for (group, members) in groups {
+            listeners.extend(members.members().map(|(rand_id, listener)| {
+                ListenerInfo::new(rand_id, Some(group.clone()), listener.as_ref())
+            }));
+        }
+        listeners
+    }
+
+    pub(crate) fn set_partitioner(&mut self, partitioner: Partitioner<T>) {
+        self.partitioner = Some(partitioner);
+    }
+
+    /// ticket of the partition which the event belongs to, if the topic is partitioned
+    pub(crate) fn partition_ticket(&mut self, event: &Event<T>) -> Option<Ticket> {
+        self.partitioner
+            .as_mut()
+            .map(|partitioner| partitioner.ticket(&**event))
+    }
+
+    pub(crate) fn set_mailbox(&mut self, mailbox: Mailbox) {
+        self.mailbox = Some(mailbox);
+    }
+
+    /// number of events waiting in the mailbox, if the topic is queued
+    pub(crate) fn queue_depth(&self) -> Option<usize> {
+        self.mailbox.as_ref().map(Mailbox::depth)
+    }
+
+    /// queue of the mailbox, if the topic is queued
+    pub(crate) fn mailbox(&self) -> Option<Arc<MailboxQueue<T>>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.mailbox.as_ref().and_then(Mailbox::queue)
+    }
+
+    pub(crate) fn set_fanout(&mut self, fanout: Fanout<T>) {
+        self.fanout = Some(fanout);
+    }
+
+    /// plain listeners with their ids
+    fn plain(&self) -> Vec<(u64, Arc<dyn Listener<T>>)> {
+        self.listeners
+            .iter()
+            .map(|(rand_id, listener)| (*rand_id, listener.clone()))
+            .collect()
+    }
+
+    /// snapshot of the listeners, if every listener receives every event without any lock
+    fn plain_snapshot(&self) -> Option<Snapshot<T>> {
+        let plain = self.groups.is_empty()
+            && self.partitioner.is_none()
+            && self.mailbox.is_none()
+            && self.fanout.is_none();
+        plain.then(|| Arc::new(self.plain()))
+    }
+
+    pub(crate) fn dispatch_plan(&self, event: &Event<T>) -> DispatchPlan<T> {
+        DispatchPlan {
+            listeners: Arc::new(self.plain()),
+            groups: self
+                .groups
+                .iter()
+                .map(|(name, group)| (name.clone(), group.candidates(event)))
+                .filter(|(_, candidates)| !candidates.is_empty())
+                .collect(),
+            fanout: self.fanout.clone(),
+        }
+    }
+}
+
+impl<T> Default for TopicListeners<T> {
+    fn default() -> Self {
+        Self {
+            listeners: HashMap::new(),
+            groups: HashMap::new(),
+            partitioner: None,
+            mailbox: None,
+            fanout: None,
+        }
+    }
+}
+
+impl<T> TopicEntry<T> {
+    pub(crate) fn new(node: Arc<TopicNode>) -> Self {
+        Self {
+            node,
+            listeners: Mutex::new(TopicListeners::default()),
+            snapshot: ArcSwapAny::from(Some(Arc::new(Vec::new()))),
+        }
+    }
+
+    /// the interned key of the topic
+    pub(crate) fn node(&self) -> &TopicNode {
+        &self.node
+    }
+
+    /// lock the listeners
+    pub async fn lock(&self) -> TopicGuard<'_, T> {
+        TopicGuard::new(self.listeners.lock().await, &self.snapshot)
+    }
+
+    /// lock the listeners if they are not locked already
+    pub fn try_lock(&self) -> Option<TopicGuard<'_, T>> {
+        let guard = self.listeners.try_lock().ok()?;
+        Some(TopicGuard::new(guard, &self.snapshot))
+    }
+
+    /// every listener of the topic, `None` if delivering an event needs the lock
+    pub(crate) fn snapshot(&self) -> Option<Snapshot<T>> {
+        self.snapshot.load_full()
+    }
+}
+
+impl<T> Debug for TopicEntry<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(format!("TopicEntry<{}>", std::any::type_name::<T>()).as_str())
+            .field("node", &self.node)
+            .field("listeners", &self.listeners)
+            .field("snapshot", &self.snapshot.load().as_ref().map(|s| s.len()))
+            .finish()
+    }
+}
+
+impl<'a, T> TopicGuard<'a, T> {
+    fn new(
+        guard: MutexGuard<'a, TopicListeners<T>>,
+        snapshot: &'a ArcSwapAny<Option<Snapshot<T>>>,
+    ) -> Self {
+        Self {
+            guard,
+            snapshot,
+            dirty: false,
+        }
+    }
+}
+
+impl<T> Deref for TopicGuard<'_, T> {
+    type Target = TopicListeners<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for TopicGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.dirty = true;
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for TopicGuard<'_, T> {
+    fn drop(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        // refreshed before the lock is released, so snapshots are stored in order
+        let snapshot = self.guard.plain_snapshot();
+        if snapshot.is_some() || self.snapshot.load().is_some() {
+            self.snapshot.store(snapshot);
+        }
+    }
+}
+
+impl<T> Debug for TopicGuard<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.guard, f)
+    }
+}
+
+impl<T> Debug for TopicListeners<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut f =
+            f.debug_struct(format!("TopicListeners<{}>", std::any::type_name::<T>()).as_str());
+        f.field("listeners", &self.listeners.keys().collect::<Vec<_>>())
+            .field("groups", &self.groups);
+        f.field("partitioner", &self.partitioner)
+            .field("mailbox", &self.mailbox)
+            .field("fanout", &self.fanout);
+        f.finish()
+    }
+}
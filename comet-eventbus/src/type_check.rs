@@ -0,0 +1,148 @@
+use crate::{PostError, TopicKey};
+use std::any::{type_name, TypeId};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// How an `Eventbus` reacts to a `TopicKey` used with another message type than the first one
+///
+/// Listeners are looked up by message type first, so a message posted to a topic whose
+/// listeners expect another type silently reaches nobody unless the type check is on.
+///
+/// ## Example:
+/// ```
+/// use comet_eventbus::{Eventbus, TypeCheck};
+///
+/// let eventbus = Eventbus::with_type_check(TypeCheck::Warn);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TypeCheck {
+    /// do not track the message types of topics
+    #[default]
+    Off,
+    /// log a warning on mismatch
+    Warn,
+    /// reject mismatched posts, `try_register` and `try_create_topic` with
+    /// `PostError::TypeMismatch`, other mismatched registrations are logged and skipped
+    Deny,
+}
+
+/// A topic key used with more than one message type
+#[derive(Debug, Clone)]
+pub struct TypeConflict {
+    /// key of the topic
+    pub key: TopicKey,
+    /// rust type names the key was used with, the first one is the bound type
+    pub type_names: Vec<&'static str>,
+}
+
+/// Message types bound to topic keys
+///
+/// A key is bound by the first `Topic` or listener created for it, and released along with
+/// that topic. Posts are checked against the binding, but never bind a key themselves.
+#[derive(Debug, Default)]
+pub(crate) struct TypeRegistry {
+    mode: TypeCheck,
+    types: Mutex<HashMap<TopicKey, Binding>>,
+}
+
+/// The message type bound to a key, and the other types it was used with
+#[derive(Debug)]
+struct Binding {
+    bound: (TypeId, &'static str),
+    rejected: Vec<(TypeId, &'static str)>,
+}
+
+impl TypeRegistry {
+    pub(crate) fn new(mode: TypeCheck) -> Self {
+        Self {
+            mode,
+            types: Default::default(),
+        }
+    }
+
+    /// bind the topic to `T` if it is not bound yet, then check `T` against the binding
+    ///
+    /// Fails if the topic is bound to another type and the mode is `TypeCheck::Deny`.
+    pub(crate) fn bind<T: 'static>(&self, key: &TopicKey) -> Result<(), PostError> {
+        if self.mode == TypeCheck::Off {
+            return Ok(());
+        }
+        let mut types = self.types.lock().unwrap();
+        types
+            .entry(key.clone())
+            .or_insert_with(|| Binding {
+                bound: (TypeId::of::<T>(), type_name::<T>()),
+                rejected: Vec::new(),
+            })
+            .check::<T>(key, self.mode)
+    }
+
+    /// check `T` against the type the topic is bound to, if any
+    ///
+    /// Fails if the topic is bound to another type and the mode is `TypeCheck::Deny`.
+    pub(crate) fn check<T: 'static>(&self, key: &TopicKey) -> Result<(), PostError> {
+        if self.mode == TypeCheck::Off {
+            return Ok(());
+        }
+        let mut types = self.types.lock().unwrap();
+        match types.get_mut(key) {
+            Some(binding) => binding.check::<T>(key, self.mode),
+            None => Ok(()),
+        }
+    }
+
+    /// unbind the topic of a dropped topic entry, if it is bound to `T`
+    pub(crate) fn release<T: 'static>(&self, key: &TopicKey) {
+        if self.mode == TypeCheck::Off {
+            return;
+        }
+        let mut types = self.types.lock().unwrap();
+        if types
+            .get(key)
+            .is_some_and(|binding| binding.bound.0 == TypeId::of::<T>())
+        {
+            types.remove(key);
+        }
+    }
+
+    pub(crate) fn conflicts(&self) -> Vec<TypeConflict> {
+        let types = self.types.lock().unwrap();
+        let mut conflicts: Vec<_> = types
+            .iter()
+            .filter(|(_, binding)| !binding.rejected.is_empty())
+            .map(|(key, binding)| TypeConflict {
+                key: key.clone(),
+                type_names: std::iter::once(&binding.bound)
+                    .chain(&binding.rejected)
+                    .map(|(_, name)| *name)
+                    .collect(),
+            })
+            .collect();
+        conflicts.sort_by(|a, b| a.key.as_ref().cmp(b.key.as_ref()));
+        conflicts
+    }
+}
+
+impl Binding {
+    fn check<T: 'static>(&mut self, key: &TopicKey, mode: TypeCheck) -> Result<(), PostError> {
+        let found = (TypeId::of::<T>(), type_name::<T>());
+        if self.bound == found {
+            return Ok(());
+        }
+        if !self.rejected.contains(&found) {
+            self.rejected.push(found);
+        }
+        let mismatch = PostError::TypeMismatch {
+            topic: key.clone(),
+            expected: self.bound.1,
+            found: found.1,
+        };
+        match mode {
+            TypeCheck::Deny => Err(mismatch),
+            _ => {
+                warn!("{}", mismatch);
+                Ok(())
+            }
+        }
+    }
+}
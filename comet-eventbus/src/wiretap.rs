@@ -0,0 +1,107 @@
+use crate::{Event, PostError, TopicKey};
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Debug tap which sees every post of an `Eventbus`, across all message types and topics
+///
+/// It is called synchronously on the posting task, so it should be cheap,
+/// e.g. push the record to a channel or a buffer.
+pub trait Wiretap: Send + Sync + 'static {
+    /// called once a post is handled
+    fn on_post(&self, record: &PostRecord<'_>);
+}
+
+/// A post seen by a `Wiretap`
+#[derive(Debug)]
+pub struct PostRecord<'a> {
+    /// key of the topic
+    pub topic: &'a TopicKey,
+    /// rust type name of the message
+    pub type_name: &'static str,
+    /// `Debug` rendering of the message, if enabled by `Eventbus::wiretap_debug`
+    pub message: Option<String>,
+    /// number of listeners subscribed to the topic
+    pub listeners: usize,
+    /// outcome of the post
+    pub outcome: PostOutcome<'a>,
+}
+
+/// Outcome of a post seen by a `Wiretap`
+#[derive(Debug)]
+pub enum PostOutcome<'a> {
+    /// the event is delivered, `failures` listeners or consumer groups failed to process it
+    Delivered {
+        /// number of failed listeners and consumer groups
+        failures: usize,
+    },
+    /// the event is queued in the mailbox of the topic
+    Queued,
+    /// the post is rejected
+    Rejected(&'a PostError),
+}
+
+type Render = fn(&dyn Any) -> String;
+
+/// Wiretap slot of an `Eventbus`, only an atomic load when no wiretap is set
+#[derive(Default)]
+pub(crate) struct Tap {
+    enabled: AtomicBool,
+    wiretap: RwLock<Option<Arc<dyn Wiretap>>>,
+    renders: RwLock<HashMap<TypeId, Render>>,
+}
+
+impl Tap {
+    pub(crate) fn set(&self, wiretap: Option<Arc<dyn Wiretap>>) {
+        let mut slot = self.wiretap.write().unwrap();
+        self.enabled.store(wiretap.is_some(), Ordering::Release);
+        *slot = wiretap;
+    }
+
+    pub(crate) fn render<T: Debug + 'static>(&self) {
+        self.renders
+            .write()
+            .unwrap()
+            .insert(TypeId::of::<T>(), |message| {
+                format!("{:?}", message.downcast_ref::<T>().unwrap())
+            });
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn record<T: 'static>(
+        &self,
+        event: &Event<T>,
+        listeners: usize,
+        outcome: PostOutcome<'_>,
+    ) {
+        let Some(wiretap) = self.wiretap.read().unwrap().clone() else {
+            return;
+        };
+        let render = self
+            .renders
+            .read()
+            .unwrap()
+            .get(&TypeId::of::<T>())
+            .copied();
+        wiretap.on_post(&PostRecord {
+            topic: event.get_key(),
+            type_name: type_name::<T>(),
+            message: render.map(|render| render(&**event)),
+            listeners,
+            outcome,
+        });
+    }
+}
+
+impl Debug for Tap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tap")
+            .field("enabled", &self.is_enabled())
+            .finish()
+    }
+}
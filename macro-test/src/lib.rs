@@ -1,6 +1,25 @@
 use ce_macros::service;
 
 #[service(MyService2, "topic")]
-async fn my_service2(arg0: u8, arg1: String, arg2: Vec<u8>) {
-    format!("{}, {}, {:?}", arg0, arg1, arg2);
-}
\ No newline at end of file
+async fn my_service2(arg0: u8, arg1: String, arg2: Vec<u8>) -> String {
+    format!("{}, {}, {:?}", arg0, arg1, arg2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use comet_eventbus::Eventbus;
+
+    #[tokio::test]
+    async fn test_call() {
+        let service = MyService2::new(Eventbus::new());
+        service.register().await;
+        assert_eq!(
+            service
+                .call(1, "foo".to_string(), vec![2, 3])
+                .await
+                .unwrap(),
+            "1, foo, [2, 3]"
+        );
+    }
+}